@@ -1,390 +1,3568 @@
-use crate::config::{self, default_num_cores, UserSetup};
-use crate::solver::{ResidualData, SolverMessage, StepInfo};
+use crate::config::{
+    self, default_num_cores, ParseSource, Profiles, RunOutcome, RunRecord, SavedFilter, Theme,
+    UserSetup,
+};
+use crate::solver::{
+    diagnostic_severity, Diagnostic, MessageSender, ResidualData, ResidualKind, Severity,
+    SolverMessage, StepInfo, TimedMessage,
+};
 use eframe::egui;
-use egui_plot::{Line, Plot, PlotPoints};
+use egui_plot::{Bar, BarChart, HLine, Line, LineStyle, Plot, PlotPoints};
+use serde::Serialize;
 use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
     fs,
-    path::PathBuf,
-    process::Child,
+    path::{Path, PathBuf},
+    process::{Child, ExitStatus},
     sync::{
         mpsc::{self, Receiver},
         Arc, Mutex,
     },
-    time::Instant,
+    time::{Duration, Instant, SystemTime},
 };
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 pub enum Ansicht {
     SolverOutput,
     Overview,
+    Problems,
+    History,
+}
+
+/// Machine-readable outcome of a run, written to `<job>.summary.json`.
+#[derive(Serialize)]
+struct RunSummary<'a> {
+    job: &'a str,
+    success: bool,
+    /// `None` if the run was stopped or ccx was terminated by a signal.
+    exit_code: Option<i32>,
+    wall_time_secs: f64,
+    final_residual: Option<f64>,
+    steps: usize,
+    increments: u32,
+    cutbacks: u32,
+    errors: usize,
+    warnings: usize,
+}
+
+/// A run and the settings it is started with. Kept after starting, so the run
+/// can be repeated as is even if the settings have changed since.
+#[derive(Clone)]
+struct RunRequest {
+    inp_path: PathBuf,
+    setup: UserSetup,
+    input_check: bool,
+}
+
+/// Identifies one line of the residual plot; lines are drawn in key order.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct ResidualKey {
+    kind: ResidualKind,
+    is_correction: bool,
+    step: u32,
+    increment: u32,
+    attempt: u32,
+}
+
+/// Options given on the command line.
+#[derive(Default)]
+pub struct LaunchOptions {
+    /// Input deck to select; its directory becomes the project directory.
+    pub inp_file: Option<PathBuf>,
+    /// Start the analysis of `inp_file` right away.
+    pub run: bool,
+}
+
+/// Color of the banner shown after a successful run.
+const SUCCESS_COLOR: egui::Color32 = egui::Color32::from_rgb(0x2e, 0xa0, 0x43);
+
+/// How often the result file sizes are polled while running.
+const RESULT_SIZE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Result files whose growth is shown as a progress proxy.
+const RESULT_FILE_EXTENSIONS: [&str; 2] = ["frd", "dat"];
+
+/// How many levels of subdirectories a recursive scan for `.inp` files descends.
+const MAX_SCAN_DEPTH: usize = 3;
+
+/// Runtime limit suggested when the limit is first enabled.
+const DEFAULT_MAX_RUNTIME_SECS: u64 = 3600;
+
+/// Residual target suggested when the target line is first enabled.
+const DEFAULT_RESIDUAL_TARGET: f64 = 1e-3;
+
+/// How long a killed solver may keep its output open before the run is ended anyway.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runtime after which stopping asks for confirmation first.
+const CONFIRM_STOP_AFTER: Duration = Duration::from_secs(60);
+
+/// Repeats the last run, whatever is selected.
+const RERUN_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::SHIFT, egui::Key::F5);
+
+/// Starts the analysis, like F5.
+const RUN_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::R);
+
+/// Shortcuts that switch to the tab with the same position.
+const TAB_SHORTCUTS: [(egui::Key, Ansicht); 4] = [
+    (egui::Key::Num1, Ansicht::SolverOutput),
+    (egui::Key::Num2, Ansicht::Overview),
+    (egui::Key::Num3, Ansicht::Problems),
+    (egui::Key::Num4, Ansicht::History),
+];
+
+/// Silence after which the running spinner turns into a waiting hint.
+const OUTPUT_STALL_HINT: Duration = Duration::from_secs(3);
+
+/// Iterations of an increment above which its step is tinted as struggling.
+const MANY_ITERATIONS: u32 = 8;
+
+/// Characters after which an output line is cut off until it is expanded.
+const LONG_LINE_CHARS: usize = 2000;
+
+/// One-click output filters as `(label, query)`. Active presets are OR-ed together.
+const FILTER_PRESETS: [(&str, &str); 5] = [
+    ("Errors", "*error"),
+    ("Warnings", "*warning"),
+    (
+        "Convergence",
+        "convergence | largest residual | largest increment",
+    ),
+    ("Contact", "contact"),
+    (
+        "Timing",
+        "actual step time | actual total time | cpu time | time needed | calculix time",
+    ),
+];
+
+/// Smoothing factor of the exponential moving average used for the ETA.
+const ETA_SMOOTHING: f64 = 0.3;
+
+/// Estimates the remaining runtime of the current step from how much wall
+/// time each unit of simulated step time has taken so far.
+#[derive(Default)]
+struct EtaEstimator {
+    last_sample: Option<(Instant, f64)>,
+    secs_per_time_unit: Option<f64>,
+}
+
+impl EtaEstimator {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn record(&mut self, now: Instant, step_time: f64) {
+        if let Some((last_instant, last_step_time)) = self.last_sample {
+            if step_time <= last_step_time {
+                return;
+            }
+            let rate =
+                now.duration_since(last_instant).as_secs_f64() / (step_time - last_step_time);
+            self.secs_per_time_unit = Some(match self.secs_per_time_unit {
+                Some(average) => ETA_SMOOTHING * rate + (1.0 - ETA_SMOOTHING) * average,
+                None => rate,
+            });
+        }
+        self.last_sample = Some((now, step_time));
+    }
+
+    fn remaining_secs(&self, info: &StepInfo) -> Option<f64> {
+        let period = info.step_period?;
+        Some((period - info.step_time).max(0.0) * self.secs_per_time_unit?)
+    }
+}
+
+/// Lays out `line` in `font_id`, marking every ASCII case-insensitive occurrence of `term`.
+fn highlighted_line(
+    line: &str,
+    term: &str,
+    font_id: egui::FontId,
+    color: egui::Color32,
+    highlight: egui::Color32,
+) -> egui::text::LayoutJob {
+    let plain = egui::TextFormat {
+        font_id,
+        color,
+        ..Default::default()
+    };
+    let marked = egui::TextFormat {
+        background: highlight,
+        ..plain.clone()
+    };
+    let mut job = egui::text::LayoutJob::default();
+    // ASCII lowercasing keeps byte offsets identical to `line`.
+    let haystack = line.to_ascii_lowercase();
+    let needle = term.to_ascii_lowercase();
+    let mut pos = 0;
+    while let Some(offset) = haystack[pos..].find(&needle) {
+        let start = pos + offset;
+        let end = start + needle.len();
+        job.append(&line[pos..start], 0.0, plain.clone());
+        job.append(&line[start..end], 0.0, marked.clone());
+        pos = end;
+    }
+    job.append(&line[pos..], 0.0, plain);
+    job
+}
+
+/// An unset working directory is valid; ccx then runs in the input's folder.
+fn output_dir_valid(setup: &UserSetup) -> bool {
+    setup.output_dir.as_ref().is_none_or(|dir| {
+        fs::metadata(dir).is_ok_and(|m| m.is_dir() && !m.permissions().readonly())
+    })
+}
+
+/// A drop-down like `egui::ComboBox`, but its popup only closes on clicks
+/// outside of it, so it can hold e.g. a text field. Close it from inside
+/// with `close_popup`.
+fn sticky_combo_box(
+    ui: &mut egui::Ui,
+    id_source: &str,
+    selected_text: String,
+    min_width: f32,
+    add_contents: impl FnOnce(&mut egui::Ui),
+) {
+    let popup_id = ui.make_persistent_id(id_source);
+    let response = ui.add(
+        egui::Button::new(format!("{} ⏷", selected_text)).min_size(egui::vec2(min_width, 0.0)),
+    );
+    if response.clicked() {
+        ui.memory_mut(|m| m.toggle_popup(popup_id));
+    }
+    egui::popup_below_widget(
+        ui,
+        popup_id,
+        &response,
+        egui::PopupCloseBehavior::CloseOnClickOutside,
+        add_contents,
+    );
+}
+
+/// Path of an input file relative to the project directory, for display.
+fn relative_display(path: &Path, base: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+/// How long ago something happened, in its largest unit: up to weeks, since
+/// the run history goes back that far.
+fn format_age(secs: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [(7 * 86400, "w"), (86400, "d"), (3600, "h"), (60, "m")];
+    UNITS
+        .iter()
+        .find(|(unit, _)| secs >= *unit)
+        .map_or("<1m".to_string(), |(unit, suffix)| {
+            format!("{}{}", secs / unit, suffix)
+        })
+}
+
+/// Columns of the step table exports.
+const STEP_TABLE_HEADER: [&str; 9] = [
+    "Step",
+    "Increment",
+    "Attempt",
+    "Iterations",
+    "Step Time",
+    "Total Time",
+    "dt",
+    "Cutbacks",
+    "Method",
+];
+
+fn step_table_rows(steps: &[StepInfo], float: fn(f64) -> String) -> Vec<[String; 9]> {
+    steps
+        .iter()
+        .map(|info| {
+            [
+                info.step.to_string(),
+                info.increment.to_string(),
+                info.attempt.to_string(),
+                info.iterations.to_string(),
+                float(info.step_time),
+                float(info.total_time),
+                float(info.dtime),
+                info.cutbacks.to_string(),
+                info.method.clone().unwrap_or_default(),
+            ]
+        })
+        .collect()
+}
+
+fn steps_to_csv(steps: &[StepInfo]) -> String {
+    let mut csv = STEP_TABLE_HEADER.join(",") + "\n";
+    for row in step_table_rows(steps, |value| value.to_string()) {
+        csv += &(row.join(",") + "\n");
+    }
+    csv
+}
+
+fn steps_to_markdown(steps: &[StepInfo]) -> String {
+    let mut markdown = format!("| {} |\n", STEP_TABLE_HEADER.join(" | "));
+    markdown += &format!("|{}\n", "---|".repeat(STEP_TABLE_HEADER.len()));
+    for row in step_table_rows(steps, |value| format!("{:.4e}", value)) {
+        markdown += &format!("| {} |\n", row.join(" | "));
+    }
+    markdown
+}
+
+/// Plot color of a step, spread around the hue circle by the golden ratio.
+fn step_color(step: u32) -> egui::Color32 {
+    let hue = (step as f32 * 0.618_034).fract();
+    egui::epaint::Hsva::new(hue, 0.85, 0.5, 1.0).into()
 }
 
 pub struct MainApp {
+    /// Working copy of the active profile; written back into `profiles` on save.
     user_setup: UserSetup,
+    profiles: Profiles,
+    profile_name_input: String,
+    /// Shown in the settings when the config couldn't be loaded.
+    config_warning: Option<String>,
+    config_saver: config::ConfigSaver,
+    /// Error of the last config save, shown in the settings until a save succeeds.
+    config_save_error: Option<String>,
+    /// Version reported by `ccx -v`, once known.
+    ccx_version: Option<String>,
+    version_receiver: Option<Receiver<Option<String>>>,
     ansicht: Ansicht,
     solver_process: Option<Arc<Mutex<Child>>>,
-    line_receiver: Option<Receiver<SolverMessage>>,
+    line_receiver: Option<Receiver<TimedMessage>>,
     is_running: bool,
-    solver_output_buffer: Vec<String>,
+    /// When the solver was killed, while its remaining output is drained.
+    stopping_since: Option<Instant>,
+    /// Whether the "really stop?" confirmation is open.
+    confirm_stop: bool,
+    /// Output lines with the seconds since the start of the run at which they arrived.
+    solver_output_buffer: VecDeque<(f32, String)>,
+    /// Number of lines dropped from the front of `solver_output_buffer`. A
+    /// line's number within the run is this plus its index in the buffer.
+    truncated_lines: usize,
+    stderr_buffer: Vec<String>,
+    show_stderr_pane: bool,
+    diagnostics: Vec<Diagnostic>,
     residual_data: Vec<ResidualData>,
     step_info: Vec<StepInfo>,
     available_inp_files: Vec<PathBuf>,
+    /// Why the project directory couldn't be scanned, as opposed to it having no decks.
+    inp_scan_error: Option<String>,
     selected_inp_file: Option<PathBuf>,
+    last_run: Option<RunRequest>,
     start_time: Option<Instant>,
-    filter_query: String,
+    /// When the solver last printed a line, to notice a hung binary.
+    last_line_time: Option<Instant>,
+    filter_name_input: String,
+    residual_log_scale: bool,
+    /// Plot the corrections next to the residuals, if the `.cvg` file provides them.
+    show_corrections: bool,
+    extra_args_text: String,
+    eta: EtaEstimator,
+    /// Job name of the current (or last) run.
+    current_job: Option<String>,
+    /// Directory the current (or last) run was started in.
+    current_run_dir: Option<PathBuf>,
+    /// Log file of the current (or last) run, if it was logged.
+    current_log_path: Option<PathBuf>,
+    result_file_sizes: Vec<(String, u64)>,
+    last_size_poll: Option<Instant>,
+    /// Set once the directory of the running job has disappeared.
+    run_dir_missing: bool,
+    /// Whether CalculiX printed its "Job finished" line during the run.
+    saw_job_finished: bool,
+    /// Exit status of the last run, if it ran to completion.
+    exit_status: Option<ExitStatus>,
+    find_query: String,
+    /// Line number of the current find match, counted like `truncated_lines`.
+    find_row: Option<usize>,
+    scroll_to_find: bool,
+    regex_filter: bool,
+    /// Compiled regex filter, keyed on the query it was built from.
+    filter_regex: Option<(String, Result<regex::Regex, regex::Error>)>,
+    last_residual: Option<f64>,
+    consecutive_residual_increases: u32,
+    /// Keep the output scrolled to the newest line.
+    auto_scroll: bool,
+    scroll_to_bottom: bool,
+    /// Whether the output was scrolled to the bottom last frame.
+    output_at_bottom: bool,
+    /// Input files ticked for the batch queue.
+    queue_selection: Vec<PathBuf>,
+    /// Queued jobs that haven't been started yet.
+    job_queue: VecDeque<PathBuf>,
+    /// Number of jobs in the running batch, or 0 outside of a batch.
+    queue_total: usize,
+    /// Anchor and end line number of the selected range. Line numbers don't
+    /// change with the filter or when older lines are dropped.
+    selected_rows: Option<(usize, usize)>,
+    /// Line numbers of the long lines that are shown in full.
+    expanded_rows: HashSet<usize>,
+    physical_cores: usize,
+    show_about: bool,
+    /// Whether the current or last run only checks the input deck.
+    input_check: bool,
+    /// `*INCLUDE`d files found missing when Run was clicked; the run waits for confirmation.
+    missing_includes: Vec<PathBuf>,
+    /// Run that waits for confirmation because of `missing_includes`.
+    include_prompt: Option<RunRequest>,
+    /// Run that waits for confirmation because its results are up to date.
+    overwrite_prompt: Option<RunRequest>,
+    /// Generated files offered for deletion by Clean, with whether each is ticked.
+    clean_selection: Option<Vec<(PathBuf, bool)>>,
+    input_check_reached_step: bool,
+    /// Prefix output lines with the time since the start of the run.
+    show_timestamps: bool,
+    /// Show the output in one collapsible section per step.
+    group_by_step: bool,
+    /// Step number and output line index (counting dropped lines) where each step starts.
+    step_starts: Vec<(u32, usize)>,
+    num_equations: Option<u64>,
+    /// Nonzero entries of the lower triangular stiffness matrix.
+    num_nonzeros: Option<u64>,
+    memory_needed_mb: Option<f64>,
+    memory_used_mb: Option<f64>,
+    /// Narrows the entries shown in the input file drop-down.
+    inp_search: String,
+    /// Theme setting and system theme the current visuals were built from.
+    applied_theme: Option<(Theme, Option<eframe::Theme>)>,
+    current_inp_file: Option<PathBuf>,
+    /// Past runs, newest first.
+    run_history: Vec<RunRecord>,
+    /// Increment number and wall-clock seconds of every finished increment.
+    increment_timings: Vec<(u32, f64)>,
+    /// Step, increment and iteration count of every converged increment.
+    increment_iterations: Vec<(u32, u32, u32)>,
+    /// Phase and seconds of the timings ccx reported.
+    solver_timings: Vec<(String, f64)>,
+    /// Wall-clock duration of the last finished run.
+    wall_time_secs: Option<f64>,
 }
 
 impl MainApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let mut app = Self {
-            user_setup: config::load(),
+    pub fn new(
+        cc: &eframe::CreationContext<'_>,
+        profiles: Profiles,
+        config_warning: Option<String>,
+        launch: LaunchOptions,
+    ) -> Self {
+        let mut app = Self::with_profiles(
+            profiles,
+            config_warning,
+            config::load_history(),
+            config::ConfigSaver::spawn(),
+        );
+        if let Some(inp_file) = &launch.inp_file {
+            if let Some(dir) = inp_file.parent() {
+                app.user_setup.project_dir_path = dir.to_path_buf();
+            }
+            app.selected_inp_file = Some(inp_file.clone());
+        }
+        app.refresh_inp_files();
+        app.request_ccx_version();
+        app.apply_theme(&cc.egui_ctx, cc.integration_info.system_theme);
+
+        if launch.run {
+            match (&launch.inp_file, &app.selected_inp_file) {
+                (Some(requested), Some(selected)) if requested == selected => {
+                    let request = app.run_request(selected.clone(), false);
+                    app.start_analysis(request);
+                }
+                _ => app.push_output_line("--run needs an existing '.inp' file.".to_string()),
+            }
+        }
+        app
+    }
+
+    /// The initial state, without touching the UI or starting anything.
+    /// Config and history writes go through `config_saver`.
+    fn with_profiles(
+        profiles: Profiles,
+        config_warning: Option<String>,
+        run_history: Vec<RunRecord>,
+        config_saver: config::ConfigSaver,
+    ) -> Self {
+        let user_setup = profiles.active_setup();
+        let extra_args_text = crate::solver::join_args(&user_setup.extra_args);
+        Self {
+            user_setup,
+            profiles,
+            profile_name_input: String::new(),
+            config_warning,
+            config_saver,
+            config_save_error: None,
+            ccx_version: None,
+            version_receiver: None,
             ansicht: Ansicht::SolverOutput,
             solver_process: None,
             line_receiver: None,
             is_running: false,
-            solver_output_buffer: Vec::new(),
+            solver_output_buffer: VecDeque::new(),
+            truncated_lines: 0,
+            stderr_buffer: Vec::new(),
+            show_stderr_pane: false,
+            diagnostics: Vec::new(),
             residual_data: Vec::new(),
             step_info: Vec::new(),
             available_inp_files: Vec::new(),
+            inp_scan_error: None,
             selected_inp_file: None,
+            last_run: None,
             start_time: None,
-            filter_query: String::new(),
+            last_line_time: None,
+            filter_name_input: String::new(),
+            residual_log_scale: false,
+            show_corrections: false,
+            extra_args_text,
+            eta: EtaEstimator::default(),
+            current_job: None,
+            current_run_dir: None,
+            current_log_path: None,
+            result_file_sizes: Vec::new(),
+            last_size_poll: None,
+            run_dir_missing: false,
+            saw_job_finished: false,
+            exit_status: None,
+            find_query: String::new(),
+            find_row: None,
+            scroll_to_find: false,
+            regex_filter: false,
+            filter_regex: None,
+            stopping_since: None,
+            confirm_stop: false,
+            last_residual: None,
+            consecutive_residual_increases: 0,
+            auto_scroll: true,
+            scroll_to_bottom: false,
+            output_at_bottom: true,
+            queue_selection: Vec::new(),
+            job_queue: VecDeque::new(),
+            queue_total: 0,
+            selected_rows: None,
+            expanded_rows: HashSet::new(),
+            physical_cores: num_cpus::get_physical(),
+            show_about: false,
+            input_check: false,
+            missing_includes: Vec::new(),
+            include_prompt: None,
+            overwrite_prompt: None,
+            clean_selection: None,
+            input_check_reached_step: false,
+            show_timestamps: false,
+            group_by_step: false,
+            step_starts: Vec::new(),
+            num_equations: None,
+            num_nonzeros: None,
+            memory_needed_mb: None,
+            memory_used_mb: None,
+            inp_search: String::new(),
+            applied_theme: None,
+            current_inp_file: None,
+            run_history,
+            increment_timings: Vec::new(),
+            increment_iterations: Vec::new(),
+            solver_timings: Vec::new(),
+            wall_time_secs: None,
+        }
+    }
+
+    /// Sets the visuals for the configured theme, unless they're already up to date.
+    fn apply_theme(&mut self, ctx: &egui::Context, system_theme: Option<eframe::Theme>) {
+        let theme = (self.user_setup.theme, system_theme);
+        if self.applied_theme == Some(theme) {
+            return;
+        }
+        let dark = match self.user_setup.theme {
+            Theme::Dark => true,
+            Theme::Light => false,
+            Theme::System => system_theme != Some(eframe::Theme::Light),
         };
-        app.refresh_inp_files();
-        app
+        ctx.set_visuals(if dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        });
+        self.applied_theme = Some(theme);
     }
 
-    fn refresh_inp_files(&mut self) {
-        self.available_inp_files.clear();
-        if let Ok(entries) = fs::read_dir(&self.user_setup.project_dir_path) {
-            self.available_inp_files = entries
-                .filter_map(Result::ok)
-                .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("inp"))
-                .map(|entry| entry.path())
-                .collect();
+    /// Queries the ccx version on a background thread so a slow binary can't block the UI.
+    fn request_ccx_version(&mut self) {
+        self.ccx_version = None;
+        if crate::solver::validate_ccx_setup(&self.user_setup).is_err() {
+            self.version_receiver = None;
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let setup = self.user_setup.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(crate::solver::query_ccx_version(&setup));
+        });
+        self.version_receiver = Some(receiver);
+    }
+
+    /// Writes the working `user_setup` back into its profile and queues a
+    /// save of the config. Failures show up in `config_save_error`.
+    fn save_config(&mut self) {
+        self.profiles
+            .profiles
+            .insert(self.profiles.active.clone(), self.user_setup.clone());
+        self.config_saver.request(self.profiles.clone());
+    }
+
+    fn switch_profile(&mut self, name: String) {
+        self.profiles
+            .profiles
+            .insert(self.profiles.active.clone(), self.user_setup.clone());
+        self.profiles.active = name;
+        self.load_active_profile();
+    }
+
+    fn load_active_profile(&mut self) {
+        self.user_setup = self.profiles.active_setup();
+        self.extra_args_text = crate::solver::join_args(&self.user_setup.extra_args);
+        self.selected_inp_file = None;
+        self.refresh_inp_files();
+        self.request_ccx_version();
+    }
+
+    fn profile_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Profile:");
+            let mut selected_profile = self.profiles.active.clone();
+            egui::ComboBox::from_id_source("profile_selector")
+                .selected_text(selected_profile.clone())
+                .show_ui(ui, |ui| {
+                    for name in self.profiles.profiles.keys() {
+                        ui.selectable_value(&mut selected_profile, name.clone(), name.as_str());
+                    }
+                });
+            if selected_profile != self.profiles.active {
+                self.switch_profile(selected_profile);
+            }
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.profile_name_input)
+                    .hint_text("Profile name")
+                    .desired_width(120.0),
+            );
+            let name = self.profile_name_input.trim().to_string();
+            let name_available = !name.is_empty() && !self.profiles.profiles.contains_key(&name);
+            let mut changed = false;
+
+            if ui
+                .add_enabled(name_available, egui::Button::new("New"))
+                .on_hover_text("Create a new profile from the current settings")
+                .clicked()
+            {
+                // The new profile starts as a copy of the current settings.
+                self.profiles
+                    .profiles
+                    .insert(self.profiles.active.clone(), self.user_setup.clone());
+                self.profiles.active = name.clone();
+                changed = true;
+            }
+            if ui
+                .add_enabled(name_available, egui::Button::new("Rename"))
+                .clicked()
+            {
+                self.profiles.profiles.remove(&self.profiles.active);
+                self.profiles.active = name;
+                changed = true;
+            }
+            if ui
+                .add_enabled(
+                    self.profiles.profiles.len() > 1,
+                    egui::Button::new("Delete"),
+                )
+                .clicked()
+            {
+                self.profiles.profiles.remove(&self.profiles.active);
+                if let Some(first) = self.profiles.profiles.keys().next() {
+                    self.profiles.active = first.clone();
+                }
+                self.load_active_profile();
+                changed = true;
+            }
+
+            if changed {
+                self.profile_name_input.clear();
+                self.save_config();
+            }
+        });
+    }
+
+    /// What the runs of the current project are testing. Saved with the
+    /// config, like any other setting.
+    fn project_notes(&mut self, ui: &mut egui::Ui) {
+        let dir = self.user_setup.project_dir_path.clone();
+        let mut notes = self
+            .user_setup
+            .project_notes
+            .get(&dir)
+            .cloned()
+            .unwrap_or_default();
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut notes)
+                .hint_text("e.g. reduced mesh, contact stiffness 1e5")
+                .desired_rows(4)
+                .desired_width(f32::INFINITY),
+        );
+        if response.changed() {
+            // Don't keep entries for projects whose notes were cleared.
+            if notes.is_empty() {
+                self.user_setup.project_notes.remove(&dir);
+            } else {
+                self.user_setup.project_notes.insert(dir, notes);
+            }
         }
+    }
+
+    /// Drop-down to apply, save and delete named output filters.
+    fn saved_filters_menu(&mut self, ui: &mut egui::Ui) {
+        let mut picked = None;
+        let mut removed = None;
+        let mut changed = false;
+        // Stays open while typing a name.
+        sticky_combo_box(ui, "saved_filters", "Saved".to_string(), 70.0, |ui| {
+            for (i, saved) in self.user_setup.saved_filters.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui
+                        .selectable_label(false, &saved.name)
+                        .on_hover_text(&saved.query)
+                        .clicked()
+                    {
+                        picked = Some(i);
+                    }
+                    if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if !self.user_setup.saved_filters.is_empty() {
+                ui.separator();
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.filter_name_input)
+                        .hint_text("Name")
+                        .desired_width(100.0),
+                );
+                let name = self.filter_name_input.trim().to_string();
+                if ui
+                    .add_enabled(
+                        !name.is_empty() && !self.user_setup.last_filter.is_empty(),
+                        egui::Button::new("Save current"),
+                    )
+                    .clicked()
+                {
+                    // Saving under an existing name replaces that filter.
+                    self.user_setup.saved_filters.retain(|f| f.name != name);
+                    self.user_setup.saved_filters.push(SavedFilter {
+                        name,
+                        query: self.user_setup.last_filter.clone(),
+                        regex: self.regex_filter,
+                    });
+                    self.filter_name_input.clear();
+                    changed = true;
+                }
+            });
+        });
+
+        if let Some(i) = picked {
+            let saved = &self.user_setup.saved_filters[i];
+            self.user_setup.last_filter = saved.query.clone();
+            self.regex_filter = saved.regex;
+            ui.memory_mut(|m| m.close_popup());
+        }
+        if let Some(i) = removed {
+            self.user_setup.saved_filters.remove(i);
+            changed = true;
+        }
+        if changed {
+            self.save_config();
+        }
+    }
+
+    /// Paths, run options and the input file selection.
+    fn settings(&mut self, ui: &mut egui::Ui, ccx_error: Option<&String>) {
+        if let Some(warning) = &self.config_warning {
+            ui.colored_label(ui.visuals().warn_fg_color, warning);
+        }
+        if let Some(error) = &self.config_save_error {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("Failed to save config: {}", error),
+            );
+        }
+        self.profile_settings(ui);
+        {
+            ui.label("Path to Calculix Binary");
+            ui.horizontal(|ui| {
+                let mut ccx_path_str = self.user_setup.calculix_bin_path.display().to_string();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut ccx_path_str)
+                        .desired_width(ui.available_width() - 50.0),
+                );
+                if response.changed() {
+                    self.user_setup.calculix_bin_path = PathBuf::from(ccx_path_str);
+                }
+                if response.lost_focus() {
+                    self.request_ccx_version();
+                }
+
+                if ui.button("…").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_file() {
+                        self.user_setup.calculix_bin_path = path;
+                        self.request_ccx_version();
+                    }
+                }
+            });
+            if let Some(error) = ccx_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            } else {
+                ui.horizontal(|ui| {
+                    if self.version_receiver.is_some() {
+                        ui.spinner();
+                        ui.label("Detecting CalculiX version…");
+                    } else {
+                        match &self.ccx_version {
+                            Some(version) => {
+                                ui.label(format!("CalculiX version {}", version));
+                            }
+                            None => {
+                                ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    "Couldn't detect a CalculiX version from '-v'",
+                                );
+                            }
+                        }
+                    }
+                    if ui.button("Detect version").clicked() {
+                        self.request_ccx_version();
+                    }
+                });
+            }
+        }
+        {
+            ui.label("Path to CGX Binary (optional, for viewing results)");
+            ui.horizontal(|ui| {
+                let mut cgx_path_str = self
+                    .user_setup
+                    .cgx_bin_path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut cgx_path_str)
+                        .desired_width(ui.available_width() - 50.0),
+                );
+                if response.changed() {
+                    self.user_setup.cgx_bin_path =
+                        Some(PathBuf::from(cgx_path_str)).filter(|p| !p.as_os_str().is_empty());
+                }
+
+                if ui.button("…").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_file() {
+                        self.user_setup.cgx_bin_path = Some(path);
+                    }
+                }
+            });
+        }
+        {
+            ui.label("Path to project directory");
+            ui.horizontal(|ui| {
+                let mut project_dir_str = self.user_setup.project_dir_path.display().to_string();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut project_dir_str)
+                        .desired_width(ui.available_width() - 160.0),
+                );
+                if response.changed() {
+                    self.user_setup.project_dir_path = PathBuf::from(project_dir_str);
+                    self.refresh_inp_files();
+                }
+
+                if ui
+                    .button("🗁")
+                    .on_hover_text("Open in the file manager")
+                    .clicked()
+                {
+                    let dir = self.user_setup.project_dir_path.clone();
+                    self.open_externally(&dir);
+                }
+
+                if ui.button("…").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        self.user_setup.project_dir_path = path;
+                        self.refresh_inp_files();
+                    }
+                }
+
+                let mut picked_recent = None;
+                egui::ComboBox::from_id_source("recent_projects")
+                    .selected_text("Recent")
+                    .width(70.0)
+                    .show_ui(ui, |ui| {
+                        if self.user_setup.recent_projects.is_empty() {
+                            ui.label("No recent projects.");
+                        }
+                        for dir in &self.user_setup.recent_projects {
+                            if ui
+                                .selectable_label(false, dir.display().to_string())
+                                .clicked()
+                            {
+                                picked_recent = Some(dir.clone());
+                            }
+                        }
+                    });
+                if let Some(dir) = picked_recent {
+                    self.user_setup.project_dir_path = dir;
+                    self.refresh_inp_files();
+                }
+            });
+            if !self.project_dir_valid() {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    "The project directory doesn't exist or isn't a directory.",
+                );
+            }
+        }
+        {
+            ui.label("Working directory (optional, defaults to the input's folder)")
+                .on_hover_text(
+                    "The input deck is copied here and ccx writes its results here. \
+                     Relative *INCLUDE paths in the deck are made absolute in the copy.",
+                );
+            ui.horizontal(|ui| {
+                let mut output_dir_str = self
+                    .user_setup
+                    .output_dir
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut output_dir_str)
+                        .desired_width(ui.available_width() - 50.0),
+                );
+                if response.changed() {
+                    self.user_setup.output_dir =
+                        Some(PathBuf::from(output_dir_str)).filter(|p| !p.as_os_str().is_empty());
+                }
+
+                if ui.button("…").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        self.user_setup.output_dir = Some(path);
+                    }
+                }
+            });
+            if !output_dir_valid(&self.user_setup) {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    "The working directory doesn't exist or isn't writable.",
+                );
+            }
+        }
+
+        if !self.is_running {
+            let max_cores = default_num_cores();
+            ui.horizontal(|ui| {
+                ui.label("Number of Cores:");
+                ui.add(egui::DragValue::new(self.user_setup.cores_mut()).range(1..=max_cores));
+                if self.project_dir_valid() {
+                    let dir = self.user_setup.project_dir_path.clone();
+                    let mut per_project = self.user_setup.project_cores.contains_key(&dir);
+                    if ui
+                        .checkbox(&mut per_project, "for this project only")
+                        .on_hover_text(format!(
+                            "Other projects use {} cores",
+                            self.user_setup.num_cores
+                        ))
+                        .changed()
+                    {
+                        if per_project {
+                            let cores = self.user_setup.num_cores;
+                            self.user_setup.project_cores.insert(dir, cores);
+                        } else {
+                            self.user_setup.project_cores.remove(&dir);
+                        }
+                    }
+                }
+                // Advisory only: `max_cores` counts logical (hyperthreaded) cores.
+                if self.user_setup.cores() > self.physical_cores {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!(
+                            "More than the {} physical cores may slow the direct solver",
+                            self.physical_cores
+                        ),
+                    );
+                }
+            });
+            // The element loop and the equation solver scale differently, so
+            // each can get its own count.
+            let num_cores = self.user_setup.cores();
+            for (label, env_var, threads) in [
+                (
+                    "Element loop threads:",
+                    "OMP_NUM_THREADS",
+                    &mut self.user_setup.omp_threads,
+                ),
+                (
+                    "Equation solver threads:",
+                    "CCX_NPROC_EQUATION_SOLVER",
+                    &mut self.user_setup.solver_threads,
+                ),
+            ] {
+                ui.horizontal(|ui| {
+                    let mut custom = threads.is_some();
+                    if ui
+                        .checkbox(&mut custom, label)
+                        .on_hover_text(format!("Sets {}", env_var))
+                        .changed()
+                    {
+                        *threads = custom.then_some(num_cores);
+                    }
+                    match threads {
+                        Some(threads) => {
+                            ui.add(egui::DragValue::new(threads).range(1..=max_cores));
+                        }
+                        None => {
+                            ui.weak(format!("same as cores ({})", num_cores));
+                        }
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut self.user_setup.inherit_thread_env,
+                    "Use inherited thread environment if set",
+                )
+                .on_hover_text(
+                    "Precedence, highest first:\n\
+                     1. Extra environment variables\n\
+                     2. OMP_NUM_THREADS, CCX_NPROC and CCX_NPROC_EQUATION_SOLVER as set \
+                     when ccx_runner was started, if this is ticked\n\
+                     3. The core and thread counts above",
+                );
+                let inherited = crate::solver::inherited_thread_env();
+                if self.user_setup.inherit_thread_env && !inherited.is_empty() {
+                    let inherited: Vec<String> = inherited
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect();
+                    ui.weak(inherited.join(" "));
+                }
+            });
+            ui.checkbox(
+                &mut self.user_setup.log_to_file,
+                "Write solver log to <job>.log",
+            );
+            ui.checkbox(
+                &mut self.user_setup.notify_on_finish,
+                "Desktop notification when the analysis finishes",
+            );
+            ui.checkbox(
+                &mut self.user_setup.write_summary,
+                "Write a run summary to <job>.summary.json",
+            );
+            ui.checkbox(
+                &mut self.user_setup.check_includes,
+                "Check for missing *INCLUDE files before running",
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.user_setup.echo_input, "Echo input")
+                    .on_hover_text("Copy the input deck into the output before the run starts");
+                ui.add_enabled(
+                    self.user_setup.echo_input,
+                    egui::Checkbox::new(&mut self.user_setup.echo_includes, "Follow *INCLUDEs"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut self.user_setup.kill_on_divergence,
+                    "Kill on divergence after",
+                );
+                ui.add_enabled(
+                    self.user_setup.kill_on_divergence,
+                    egui::DragValue::new(&mut self.user_setup.divergence_threshold).range(2..=100),
+                );
+                ui.label("consecutive residual increases");
+            });
+            ui.horizontal(|ui| {
+                let mut limit_runtime = self.user_setup.max_runtime_secs.is_some();
+                if ui.checkbox(&mut limit_runtime, "Limit runtime").changed() {
+                    self.user_setup.max_runtime_secs =
+                        limit_runtime.then_some(DEFAULT_MAX_RUNTIME_SECS);
+                }
+                if let Some(secs) = &mut self.user_setup.max_runtime_secs {
+                    ui.add(
+                        egui::DragValue::new(secs)
+                            .range(1..=u64::MAX)
+                            .speed(60)
+                            .suffix(" s"),
+                    );
+                    ui.label(format!("({})", format_duration(*secs as f64)));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Warn after no output for:");
+                ui.add(
+                    egui::DragValue::new(&mut self.user_setup.silence_warning_secs)
+                        .range(1..=u64::MAX)
+                        .speed(5)
+                        .suffix(" s"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Max output lines:");
+                ui.add(
+                    egui::DragValue::new(&mut self.user_setup.max_output_lines)
+                        .range(1_000..=10_000_000)
+                        .speed(1_000),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Progress source:");
+                ui.radio_value(
+                    &mut self.user_setup.parse_source,
+                    ParseSource::Stdout,
+                    "stdout",
+                );
+                ui.radio_value(
+                    &mut self.user_setup.parse_source,
+                    ParseSource::StaFile,
+                    ".sta file",
+                )
+                .on_hover_text("Tail <job>.sta, useful when stdout is buffered by a wrapper");
+            });
+            ui.checkbox(
+                &mut self.user_setup.tail_cvg,
+                "Read residuals and corrections from <job>.cvg",
+            )
+            .on_hover_text("Covers every iteration; the values are in percent");
+            ui.horizontal(|ui| {
+                ui.label("Launcher:");
+                let mut launcher = self.user_setup.launcher.clone().unwrap_or_default();
+                let response = ui
+                    .add(
+                        egui::TextEdit::singleline(&mut launcher)
+                            .hint_text("none")
+                            .desired_width(160.0),
+                    )
+                    .on_hover_text("Runs ccx through this command, e.g. 'wsl' or 'mpirun -np 4'.");
+                if response.changed() {
+                    self.user_setup.launcher = Some(launcher).filter(|l| !l.trim().is_empty());
+                }
+                if response.lost_focus() {
+                    self.request_ccx_version();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Input flag:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.user_setup.input_flag)
+                        .hint_text("positional")
+                        .desired_width(60.0),
+                )
+                .on_hover_text("Precedes the job name. Leave empty to pass the job positionally.");
+                ui.checkbox(
+                    &mut self.user_setup.job_with_extension,
+                    "Pass the job with its .inp extension",
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Extra arguments:");
+                let response = ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.extra_args_text)
+                            .hint_text("e.g. -v")
+                            .desired_width(f32::INFINITY),
+                    )
+                    .on_hover_text(
+                        "Appended after the job. The job name always comes from the selected .inp file.",
+                    );
+                if response.changed() {
+                    self.user_setup.extra_args =
+                        crate::solver::split_args(&self.extra_args_text);
+                }
+            });
+            ui.collapsing("Environment variables", |ui| {
+                let mut remove_index = None;
+                egui::Grid::new("extra_env_grid").show(ui, |ui| {
+                    for (i, (key, value)) in self.user_setup.extra_env.iter_mut().enumerate() {
+                        ui.add(
+                            egui::TextEdit::singleline(key)
+                                .hint_text("NAME")
+                                .desired_width(180.0),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(value)
+                                .hint_text("value")
+                                .desired_width(180.0),
+                        );
+                        if ui.button("🗑").clicked() {
+                            remove_index = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = remove_index {
+                    self.user_setup.extra_env.remove(i);
+                }
+                if ui.button("Add variable").clicked() {
+                    self.user_setup
+                        .extra_env
+                        .push((String::new(), String::new()));
+                }
+            });
+        }
+
+        // Drop-down for .inp file
+        if !self.is_running {
+            let selected_file_name = self
+                .selected_inp_file
+                .as_ref()
+                .map(|p| relative_display(p, &self.user_setup.project_dir_path))
+                .unwrap_or_else(|| "Select a file".to_string());
+
+            ui.horizontal(|ui| {
+                ui.label("Input file");
+                if ui
+                    .checkbox(
+                        &mut self.user_setup.recursive_scan,
+                        "Include subdirectories",
+                    )
+                    .changed()
+                {
+                    self.refresh_inp_files();
+                }
+            });
+            // Stays open while typing into the search box.
+            sticky_combo_box(ui, "inp_file_selector", selected_file_name, 0.0, |ui| {
+                self.refresh_inp_files();
+
+                if let Some(error) = &self.inp_scan_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                } else if self.available_inp_files.is_empty() {
+                    ui.label("No .inp files found.");
+                } else {
+                    ui.add(egui::TextEdit::singleline(&mut self.inp_search).hint_text("Search…"));
+                    let search = self.inp_search.to_lowercase();
+                    // Use a scroll area in case there are many files.
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            for f in &self.available_inp_files {
+                                let file_name =
+                                    relative_display(f, &self.user_setup.project_dir_path);
+                                if !file_name.to_lowercase().contains(&search) {
+                                    continue;
+                                }
+                                if ui
+                                    .selectable_value(
+                                        &mut self.selected_inp_file,
+                                        Some(f.clone()),
+                                        file_name,
+                                    )
+                                    .clicked()
+                                {
+                                    ui.memory_mut(|mem| mem.close_popup());
+                                }
+                            }
+                        });
+                }
+            });
+
+            if let Some(inp_path) = &self.selected_inp_file {
+                let preview = crate::solver::build_command_preview(&self.user_setup, inp_path);
+                ui.collapsing("Command line", |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut preview.as_str())
+                            .font(egui::TextStyle::Monospace)
+                            .desired_rows(1)
+                            .desired_width(f32::INFINITY),
+                    );
+                    if ui.button("Copy").clicked() {
+                        ui.output_mut(|o| o.copied_text = preview.clone());
+                    }
+                });
+            }
+
+            // A fixed id keeps the header open while the count in its title changes.
+            egui::CollapsingHeader::new(format!(
+                "Batch queue ({} selected)",
+                self.queue_selection.len()
+            ))
+            .id_source("batch_queue")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("All").clicked() {
+                        self.queue_selection = self.available_inp_files.clone();
+                    }
+                    if ui.button("None").clicked() {
+                        self.queue_selection.clear();
+                    }
+                });
+                egui::ScrollArea::vertical()
+                    .id_source("queue_files")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for f in &self.available_inp_files {
+                            let file_name = relative_display(f, &self.user_setup.project_dir_path);
+                            let mut queued = self.queue_selection.contains(f);
+                            if ui.checkbox(&mut queued, file_name).changed() {
+                                if queued {
+                                    self.queue_selection.push(f.clone());
+                                } else {
+                                    self.queue_selection.retain(|q| q != f);
+                                }
+                            }
+                        }
+                    });
+            });
+        }
+    }
+
+    /// Applies a message the reader threads produced at `at`.
+    fn handle_message(&mut self, at: Instant, message: SolverMessage) {
+        match message {
+            SolverMessage::Line(line) => {
+                if line.contains("Job finished") {
+                    self.saw_job_finished = true;
+                }
+                self.last_line_time = Some(at);
+                self.push_output_line_at(at, line);
+            }
+            SolverMessage::StderrLine(line) => {
+                self.last_line_time = Some(at);
+                // Keep stderr interleaved with stdout and in its own buffer.
+                self.push_output_line_at(at, line.clone());
+                self.stderr_buffer.push(line);
+            }
+            SolverMessage::Diagnostic(diagnostic) => self.diagnostics.push(diagnostic),
+            SolverMessage::Residual(data) => {
+                // Flux residuals would interleave with the force ones, so only
+                // force residuals count towards divergence.
+                if data.kind == ResidualKind::Force {
+                    self.track_divergence(data.residual);
+                }
+                self.residual_data.push(data);
+            }
+            SolverMessage::ResetResiduals => {
+                // A new increment starts from a fresh residual. The previous
+                // increments stay in `residual_data` as their own series.
+                self.last_residual = None;
+                self.consecutive_residual_increases = 0;
+            }
+            SolverMessage::IncrementTiming { increment, secs } => {
+                self.increment_timings.push((increment, secs));
+            }
+            SolverMessage::Timing { phase, secs } => self.solver_timings.push((phase, secs)),
+            SolverMessage::ModelInfo(info) => {
+                self.num_equations = info.num_equations;
+                self.num_nonzeros = info.num_nonzeros;
+                self.memory_needed_mb = info.memory_needed_mb;
+                self.memory_used_mb = info.memory_used_mb;
+            }
+            SolverMessage::InputChecked { reached_step } => {
+                self.input_check_reached_step = reached_step;
+            }
+            SolverMessage::Finished(status) => {
+                if self.saw_job_finished {
+                    self.record_increment_iterations();
+                }
+                self.exit_status = Some(status);
+            }
+            SolverMessage::NewStepInfo(info) => {
+                // The previous step's last increment converged, or there'd be no new step.
+                self.record_increment_iterations();
+                // Sent just before the step's header line.
+                self.step_starts.push((
+                    info.step,
+                    self.truncated_lines + self.solver_output_buffer.len(),
+                ));
+                self.eta.reset();
+                self.step_info.push(info);
+            }
+            SolverMessage::UpdateStepInfo(info) => {
+                self.eta.record(at, info.step_time);
+                // Moving on to the next increment means the current one converged;
+                // a new attempt at the same increment doesn't.
+                if self
+                    .step_info
+                    .last()
+                    .is_some_and(|last| (last.step, last.increment) != (info.step, info.increment))
+                {
+                    self.record_increment_iterations();
+                }
+                if let Some(last) = self.step_info.last_mut() {
+                    *last = info;
+                }
+            }
+        }
+    }
+
+    /// Records the iterations the current increment took, once it converged.
+    fn record_increment_iterations(&mut self) {
+        let Some(info) = self.step_info.last() else {
+            return;
+        };
+        let recorded = self
+            .increment_iterations
+            .last()
+            .is_some_and(|&(step, increment, _)| (step, increment) == (info.step, info.increment));
+        // Before the first increment, and when called twice for the same one.
+        if info.increment == 0 || recorded {
+            return;
+        }
+        self.increment_iterations
+            .push((info.step, info.increment, info.iterations));
+    }
+
+    /// Stats the job's result files at most once per `RESULT_SIZE_POLL_INTERVAL`.
+    fn poll_result_file_sizes(&mut self) {
+        if self
+            .last_size_poll
+            .is_some_and(|last| last.elapsed() < RESULT_SIZE_POLL_INTERVAL)
+        {
+            return;
+        }
+        self.last_size_poll = Some(Instant::now());
+
+        let (Some(dir), Some(job)) = (&self.current_run_dir, &self.current_job) else {
+            return;
+        };
+        // E.g. a deleted project folder or a dropped network mount.
+        if !dir.is_dir() {
+            if !self.run_dir_missing {
+                self.run_dir_missing = true;
+                let message = format!(
+                    "The job directory {} has disappeared; the solver can't write its results.",
+                    dir.display()
+                );
+                self.push_output_line(message);
+            }
+            return;
+        }
+        self.result_file_sizes = RESULT_FILE_EXTENSIONS
+            .iter()
+            .filter_map(|ext| {
+                let name = format!("{}.{}", job, ext);
+                let size = fs::metadata(dir.join(&name)).ok()?.len();
+                Some((name, size))
+            })
+            .collect();
+    }
+
+    fn open_results_in_cgx(&mut self) {
+        if self.user_setup.cgx_bin_path.is_none() {
+            self.user_setup.cgx_bin_path = rfd::FileDialog::new()
+                .set_title("Select the CGX binary")
+                .pick_file();
+        }
+        let (Some(cgx_path), Some(dir), Some(job)) = (
+            &self.user_setup.cgx_bin_path,
+            &self.current_run_dir,
+            &self.current_job,
+        ) else {
+            return;
+        };
+        if let Err(e) = crate::solver::open_in_cgx(cgx_path, dir, job) {
+            self.push_output_line(format!("Failed to start CGX: {}", e));
+        }
+    }
+
+    /// The project directory must exist, since ccx runs inside it.
+    fn project_dir_valid(&self) -> bool {
+        self.user_setup.project_dir_path.is_dir()
+    }
+
+    /// Why a run can't be started right now, if anything prevents it.
+    fn run_blocker(&self) -> Option<&'static str> {
+        self.run_blocker_for(&self.user_setup)
+    }
+
+    /// Like [`Self::run_blocker`], for a run with `setup` instead of the current settings.
+    fn run_blocker_for(&self, setup: &UserSetup) -> Option<&'static str> {
+        if self.is_running {
+            Some("An analysis is already running.")
+        } else if crate::solver::validate_ccx_setup(setup).is_err() {
+            Some("Configure a valid CalculiX binary first.")
+        } else if !setup.project_dir_path.is_dir() {
+            Some("Select an existing project directory first.")
+        } else if !output_dir_valid(setup) {
+            Some("The working directory doesn't exist or isn't writable.")
+        } else {
+            None
+        }
+    }
+
+    fn can_run(&self) -> bool {
+        self.run_blocker().is_none()
+    }
+
+    fn num_errors(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count()
+    }
+
+    /// Asks where to save an export of the step table and writes it there.
+    fn export_step_table(&mut self, extension: &str, contents: String) {
+        let job = self.current_job.as_deref().unwrap_or("steps");
+        let mut dialog = rfd::FileDialog::new()
+            .set_file_name(format!("{}.steps.{}", job, extension))
+            .add_filter(extension, &[extension]);
+        if let Some(dir) = &self.current_run_dir {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.save_file() {
+            if let Err(e) = fs::write(&path, contents) {
+                self.push_output_line(format!("Failed to write {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// Opens a directory in the file manager, or a file in its default application.
+    fn open_externally(&mut self, path: &Path) {
+        if let Err(e) = open::that(path) {
+            self.push_output_line(format!("Failed to open {}: {}", path.display(), e));
+        }
+    }
+
+    fn write_summary(&mut self) {
+        let (Some(dir), Some(job)) = (&self.current_run_dir, &self.current_job) else {
+            return;
+        };
+        let errors = self.num_errors();
+        let summary = RunSummary {
+            job,
+            success: self.run_succeeded(),
+            exit_code: self.exit_status.and_then(|status| status.code()),
+            wall_time_secs: self
+                .start_time
+                .map_or(0.0, |start_time| start_time.elapsed().as_secs_f64()),
+            final_residual: self
+                .residual_data
+                .iter()
+                .rfind(|d| d.kind == ResidualKind::Force)
+                .map(|d| d.residual),
+            steps: self.step_info.len(),
+            increments: self.step_info.iter().map(|info| info.increment).sum(),
+            cutbacks: self.step_info.iter().map(|info| info.cutbacks).sum(),
+            errors,
+            warnings: self.diagnostics.len() - errors,
+        };
+        let path = dir.join(format!("{}.summary.json", job));
+        let result = serde_json::to_string_pretty(&summary)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(&path, json));
+        if let Err(e) = result {
+            self.push_output_line(format!("Failed to write {}: {}", path.display(), e));
+        }
+    }
+
+    /// Writes the summary and the history entry of a run that just ended,
+    /// before its state is reset.
+    fn run_ended(&mut self, stopped: bool) {
+        self.wall_time_secs = self
+            .start_time
+            .map(|start_time| start_time.elapsed().as_secs_f64());
+        if self.user_setup.write_summary {
+            self.write_summary();
+        }
+        if self.input_check {
+            return;
+        }
+        let (Some(inp_file), Some(job), Some(start_time)) =
+            (&self.current_inp_file, &self.current_job, self.start_time)
+        else {
+            return;
+        };
+        let outcome = if stopped {
+            RunOutcome::Stopped
+        } else if self.run_succeeded() {
+            RunOutcome::Succeeded
+        } else {
+            RunOutcome::Failed
+        };
+        let elapsed = start_time.elapsed();
+        let started = SystemTime::now()
+            .checked_sub(elapsed)
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+        let record = RunRecord {
+            job: job.clone(),
+            inp_file: inp_file.clone(),
+            started,
+            duration_secs: elapsed.as_secs_f64(),
+            outcome,
+        };
+        config::record_run(&mut self.run_history, record);
+        self.config_saver.request_history(self.run_history.clone());
+    }
+
+    /// Kills the running solver. The run keeps draining output and ends once
+    /// the readers are done, or after `STOP_TIMEOUT`.
+    fn stop_analysis(&mut self) {
+        if self.stopping_since.is_some() {
+            return;
+        }
+        let Some(process) = self.solver_process.clone() else {
+            self.end_stopped_run();
+            return;
+        };
+        if let Ok(mut child) = process.lock() {
+            match crate::solver::kill_process(&mut child) {
+                Ok(_) => {
+                    println!("Process killed");
+                }
+                Err(e) => println!("Failed to kill process: {}", e),
+            }
+        }
+        crate::solver::spawn_reaper_thread(process);
+        self.stopping_since = Some(Instant::now());
+    }
+
+    /// Stops the run and drops the queued jobs, after confirmation if the run
+    /// has been going for longer than `CONFIRM_STOP_AFTER`.
+    fn request_stop(&mut self) {
+        if self
+            .start_time
+            .is_some_and(|start_time| start_time.elapsed() >= CONFIRM_STOP_AFTER)
+        {
+            self.confirm_stop = true;
+        } else {
+            self.abort_run();
+        }
+    }
+
+    /// Stops the run and drops the queued jobs.
+    fn abort_run(&mut self) {
+        self.confirm_stop = false;
+        self.job_queue.clear();
+        self.queue_total = 0;
+        self.stop_analysis();
+    }
+
+    /// Resets the run state after a stop.
+    fn end_stopped_run(&mut self) {
+        if self.is_running {
+            self.run_ended(true);
+        }
+        self.is_running = false;
+        self.stopping_since = None;
+        self.solver_process = None;
+        self.line_receiver = None;
+        self.start_time = None;
+    }
+
+    /// A run of `inp_path` with the current settings.
+    fn run_request(&self, inp_path: PathBuf, input_check: bool) -> RunRequest {
+        RunRequest {
+            inp_path,
+            setup: self.user_setup.clone(),
+            input_check,
+        }
+    }
+
+    /// Starts `request` once the user confirmed what needs confirming: missing
+    /// `*INCLUDE`s first, then overwriting up-to-date results.
+    fn request_run(&mut self, request: RunRequest) {
+        self.missing_includes = if request.setup.check_includes {
+            // Includes resolve from the input's folder, also in a copied deck.
+            let input_dir = crate::solver::job_dir(&request.setup, &request.inp_path);
+            crate::inp::check_includes(&request.inp_path, input_dir)
+        } else {
+            Vec::new()
+        };
+        if self.missing_includes.is_empty() {
+            self.include_prompt = None;
+            self.start_unless_up_to_date(request);
+        } else {
+            self.include_prompt = Some(request);
+        }
+    }
+
+    /// Starts `request`, unless that would overwrite results that are newer
+    /// than the deck, in which case the user is asked first. Input checks
+    /// don't write results, so they always start.
+    fn start_unless_up_to_date(&mut self, request: RunRequest) {
+        if !request.input_check
+            && crate::solver::results_up_to_date(&request.setup, &request.inp_path)
+        {
+            self.overwrite_prompt = Some(request);
+        } else {
+            self.overwrite_prompt = None;
+            self.start_analysis(request);
+        }
+    }
+
+    /// Starts the last run again with the settings it was started with. The
+    /// current settings are left alone.
+    fn rerun_last(&mut self) {
+        if let Some(last_run) = self.last_run.clone() {
+            self.request_run(last_run);
+        }
+    }
+
+    /// Resets the per-run state and starts ccx as `request` describes.
+    fn start_analysis(&mut self, request: RunRequest) {
+        // Every way of starting a run ends up here, so this guard covers them all.
+        if let Some(blocker) = self.run_blocker_for(&request.setup) {
+            self.push_output_line(format!("Not starting the analysis: {}", blocker));
+            return;
+        }
+        let RunRequest {
+            inp_path,
+            setup,
+            input_check,
+        } = &request;
+        // Here rather than at the Run button, so re-runs, `--run` and queued jobs count too.
+        // Persisted by the autosave in `update`.
+        self.user_setup.remember_project(&setup.project_dir_path);
+        let job_name = crate::solver::job_name(inp_path);
+        let run_dir = crate::solver::results_dir(setup, inp_path).to_path_buf();
+        let (sender, receiver) = mpsc::channel();
+        let sender = MessageSender::new(sender);
+        self.line_receiver = Some(receiver);
+        self.is_running = true;
+        self.start_time = Some(Instant::now());
+        self.last_line_time = None;
+        self.solver_output_buffer.clear();
+        self.truncated_lines = 0;
+        self.selected_rows = None;
+        self.expanded_rows.clear();
+        self.step_starts.clear();
+        self.stderr_buffer.clear();
+        self.diagnostics.clear();
+        self.residual_data.clear();
+        self.increment_timings.clear();
+        self.increment_iterations.clear();
+        self.solver_timings.clear();
+        self.wall_time_secs = None;
+        self.step_info.clear();
+        self.eta.reset();
+        self.input_check = *input_check;
+        self.current_job = Some(job_name.to_string());
+        self.current_run_dir = Some(run_dir.clone());
+        self.current_log_path = None;
+        self.current_inp_file = Some(inp_path.clone());
+        self.result_file_sizes.clear();
+        self.last_size_poll = None;
+        self.run_dir_missing = false;
+        self.saw_job_finished = false;
+        self.exit_status = None;
+        self.input_check_reached_step = false;
+        self.num_equations = None;
+        self.num_nonzeros = None;
+        self.memory_needed_mb = None;
+        self.memory_used_mb = None;
+
+        if setup.echo_input {
+            self.echo_input_deck(inp_path, setup);
+        }
+
+        let child = crate::solver::spawn_process(setup, inp_path);
+
+        match child {
+            Ok(child) => {
+                self.last_run = Some(request.clone());
+                // Each queued job replaces the previous output, so its log is always kept.
+                let keep_log = setup.log_to_file || self.queue_total > 0;
+                let log_path = keep_log.then(|| crate::solver::log_file_path(&run_dir, job_name));
+                self.current_log_path = log_path.clone();
+                let step_periods = fs::read_to_string(inp_path)
+                    .map(|deck| crate::inp::parse_step_periods(&deck))
+                    .unwrap_or_default();
+                let options = crate::solver::ReaderOptions {
+                    log_path,
+                    step_periods: step_periods.clone(),
+                    parse_progress: setup.parse_source == ParseSource::Stdout,
+                    parse_residuals: !setup.tail_cvg,
+                    check_only: *input_check,
+                    ..Default::default()
+                };
+                let finished = options.finished.clone();
+                let process = Arc::new(Mutex::new(child));
+                let readers = crate::solver::spawn_stderr_reader_thread(
+                    &mut process.lock().unwrap(),
+                    sender.clone(),
+                )
+                .and_then(|()| {
+                    crate::solver::spawn_reader_thread(&process, sender.clone(), options)
+                });
+                match readers {
+                    Ok(()) => {
+                        if setup.parse_source == ParseSource::StaFile {
+                            crate::solver::spawn_sta_reader_thread(
+                                crate::solver::sta_file_path(&run_dir, job_name),
+                                sender.clone(),
+                                step_periods,
+                                finished.clone(),
+                            );
+                        }
+                        if setup.tail_cvg {
+                            crate::solver::spawn_cvg_reader_thread(
+                                crate::solver::cvg_file_path(&run_dir, job_name),
+                                sender,
+                                finished,
+                            );
+                        }
+                        self.solver_process = Some(process);
+                    }
+                    Err(e) => {
+                        // Without a reader nothing would ever end the run.
+                        if let Ok(mut child) = process.lock() {
+                            let _ = crate::solver::kill_process(&mut child);
+                            let _ = child.wait();
+                        }
+                        self.push_output_line(format!("Failed to read solver output: {}", e));
+                        self.is_running = false;
+                        self.line_receiver = None;
+                        self.start_time = None;
+                    }
+                }
+            }
+            Err(e) => {
+                self.push_output_line(format!("Failed to start process: {}", e));
+                self.is_running = false;
+                self.line_receiver = None;
+                self.start_time = None;
+            }
+        }
+    }
+
+    /// Counts consecutive residual increases and kills the run once the
+    /// configured threshold is reached.
+    fn track_divergence(&mut self, residual: f64) {
+        if self.last_residual.is_some_and(|last| residual > last) {
+            self.consecutive_residual_increases += 1;
+        } else {
+            self.consecutive_residual_increases = 0;
+        }
+        self.last_residual = Some(residual);
+
+        if self.user_setup.kill_on_divergence
+            && self.stopping_since.is_none()
+            && self.consecutive_residual_increases >= self.user_setup.divergence_threshold
+        {
+            self.push_output_line(format!(
+                "Killed: divergence detected ({} consecutive residual increases)",
+                self.consecutive_residual_increases
+            ));
+            self.stop_analysis();
+        }
+    }
+
+    /// Copies the input deck into the output, so a saved log shows what was solved.
+    fn echo_input_deck(&mut self, inp_path: &Path, setup: &UserSetup) {
+        let input_dir = crate::solver::job_dir(setup, inp_path);
+        match crate::inp::read_deck_lines(inp_path, input_dir, setup.echo_includes) {
+            Ok(lines) => {
+                self.push_output_line("=== INPUT ===".to_string());
+                for line in lines {
+                    self.push_output_line(line);
+                }
+                self.push_output_line("=== END INPUT ===".to_string());
+            }
+            Err(e) => {
+                self.push_output_line(format!("Could not echo {}: {}", inp_path.display(), e))
+            }
+        }
+    }
+
+    fn push_output_line(&mut self, line: String) {
+        self.push_output_line_at(Instant::now(), line);
+    }
+
+    /// Appends a line that arrived at `at`, dropping the oldest ones once
+    /// `max_output_lines` is exceeded.
+    fn push_output_line_at(&mut self, at: Instant, line: String) {
+        let elapsed = self.start_time.map_or(0.0, |start_time| {
+            at.saturating_duration_since(start_time).as_secs_f32()
+        });
+        self.solver_output_buffer.push_back((elapsed, line));
+        let max_lines = self.user_setup.max_output_lines.max(1);
+        while self.solver_output_buffer.len() > max_lines {
+            self.solver_output_buffer.pop_front();
+            self.truncated_lines += 1;
+        }
+        // Forget state that belongs to dropped lines.
+        let first_line = self.truncated_lines;
+        self.expanded_rows.retain(|&line| line >= first_line);
+        if self
+            .selected_rows
+            .is_some_and(|(anchor, end)| anchor.max(end) < first_line)
+        {
+            self.selected_rows = None;
+        }
+        if self.find_row.is_some_and(|line| line < first_line) {
+            self.find_row = None;
+        }
+    }
+
+    /// A run only counts as successful if ccx exited cleanly *and* printed "Job finished".
+    fn run_succeeded(&self) -> bool {
+        self.saw_job_finished && self.exit_status.is_some_and(|status| status.success())
+    }
+
+    fn completion_banner(&self, ui: &mut egui::Ui) {
+        let Some(status) = self.exit_status else {
+            return;
+        };
+        if self.input_check {
+            let num_errors = self.num_errors();
+            if num_errors > 0 {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("✖ Input check found {} error(s)", num_errors),
+                );
+            } else if self.input_check_reached_step {
+                ui.colored_label(SUCCESS_COLOR, "✔ Input check passed");
+            } else {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    "✖ Input check ended before the first step",
+                );
+            }
+        } else if self.run_succeeded() {
+            let duration = self
+                .wall_time_secs
+                .map(|secs| format!(" in {}", format_duration(secs)))
+                .unwrap_or_default();
+            ui.colored_label(
+                SUCCESS_COLOR,
+                format!("✔ Completed successfully{}", duration),
+            );
+        } else {
+            let reason = match status.code() {
+                Some(0) => "no 'Job finished' line".to_string(),
+                Some(code) => format!("exit code {}", code),
+                None => "terminated by signal".to_string(),
+            };
+            let duration = self
+                .wall_time_secs
+                .map(|secs| format!(" after {}", format_duration(secs)))
+                .unwrap_or_default();
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("✖ Failed ({}){}", reason, duration),
+            );
+        }
+    }
+
+    fn notify_finished(&self) {
+        let job = self.current_job.as_deref().unwrap_or("Analysis");
+        let body = if self.run_succeeded() {
+            format!("{} finished successfully.", job)
+        } else {
+            format!("{} ended without finishing. Check the solver output.", job)
+        };
+        // Notifications are best effort; platforms without support just skip them.
+        if let Err(e) = notify_rust::Notification::new()
+            .summary("CalculiX Solution Monitor")
+            .body(&body)
+            .show()
+        {
+            eprintln!("Failed to show notification: {}", e);
+        }
+    }
+
+    fn refresh_inp_files(&mut self) {
+        let max_depth = if self.user_setup.recursive_scan {
+            MAX_SCAN_DEPTH
+        } else {
+            0
+        };
+        let project_dir = &self.user_setup.project_dir_path;
+        self.inp_scan_error = match fs::read_dir(project_dir) {
+            Ok(_) => None,
+            Err(_) if project_dir.as_os_str().is_empty() => {
+                Some("No project directory set.".to_string())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Some(format!(
+                "The project directory {} doesn't exist (anymore).",
+                project_dir.display()
+            )),
+            Err(e) => Some(format!(
+                "Can't read the project directory {}: {}",
+                project_dir.display(),
+                e
+            )),
+        };
+        self.available_inp_files = crate::inp::find_inp_files(project_dir, max_depth);
+        self.available_inp_files.sort();
         // If the selected file is no longer available, reset it.
         if let Some(selected) = &self.selected_inp_file {
             if !self.available_inp_files.contains(selected) {
                 self.selected_inp_file = None;
             }
         }
-        // If nothing is selected, and there are files, select the first one.
-        if self.selected_inp_file.is_none() && !self.available_inp_files.is_empty() {
-            self.selected_inp_file = self.available_inp_files.first().cloned();
+        // If nothing is selected, restore the last run file or fall back to the first one.
+        if self.selected_inp_file.is_none() {
+            self.selected_inp_file = self
+                .user_setup
+                .last_inp_file
+                .clone()
+                .filter(|f| self.available_inp_files.contains(f))
+                .or_else(|| self.available_inp_files.first().cloned());
+        }
+        self.queue_selection
+            .retain(|f| self.available_inp_files.contains(f));
+    }
+}
+
+impl Drop for MainApp {
+    fn drop(&mut self) {
+        // Don't leave an orphaned solver running after the window is closed.
+        if let Some(process) = self.solver_process.take() {
+            if let Ok(mut process) = process.lock() {
+                if let Err(e) = crate::solver::kill_process(&mut process) {
+                    eprintln!("Failed to kill process on exit: {}", e);
+                }
+            }
         }
+        // Persist the window geometry recorded in `update`. The saver writes it
+        // when it is dropped right after this.
+        self.save_config();
     }
 }
 
 impl eframe::App for MainApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Re-applied on change, e.g. after switching profiles or when the system theme flips.
+        self.apply_theme(ctx, frame.info().system_theme);
+
+        // Remember the window geometry so it can be restored on the next launch.
+        ctx.input(|i| {
+            let viewport = i.viewport();
+            if viewport.minimized != Some(true) {
+                if let Some(rect) = viewport.inner_rect {
+                    self.user_setup.window_size = Some([rect.width(), rect.height()]);
+                }
+                if let Some(rect) = viewport.outer_rect {
+                    self.user_setup.window_pos = Some([rect.min.x, rect.min.y]);
+                }
+            }
+        });
+
+        // Settings edited in the last frame, and the geometry just recorded, are saved
+        // in the background; the saver coalesces bursts of edits.
+        if self.profiles.profiles.get(&self.profiles.active) != Some(&self.user_setup) {
+            self.save_config();
+        }
+        if let Some(result) = self.config_saver.poll() {
+            self.config_save_error = result.err().map(|e| e.to_string());
+        }
+
+        if let Some(receiver) = &self.version_receiver {
+            if let Ok(version) = receiver.try_recv() {
+                self.ccx_version = version;
+                self.version_receiver = None;
+            }
+        }
+
         // Handle solver output and check for completion
-        if let Some(receiver) = &self.line_receiver {
+        // The receiver is taken out while draining so handlers can borrow `self` mutably.
+        if let Some(receiver) = self.line_receiver.take() {
             // Use a loop to drain the channel on each frame.
             loop {
                 match receiver.try_recv() {
-                    Ok(message) => match message {
-                        SolverMessage::Line(line) => {
-                            self.solver_output_buffer.push(line);
-                        }
-                        SolverMessage::Residual(data) => self.residual_data.push(data),
-                        SolverMessage::ResetResiduals => self.residual_data.clear(),
-                        SolverMessage::NewStepInfo(info) => self.step_info.push(info),
-                        SolverMessage::UpdateStepInfo(info) => {
-                            if let Some(last) = self.step_info.last_mut() {
-                                *last = info;
-                            }
-                        }
-                    },
+                    Ok(TimedMessage { at, message }) => self.handle_message(at, message),
                     Err(mpsc::TryRecvError::Empty) => {
                         // No more messages in the channel for now.
                         break;
                     }
                     Err(mpsc::TryRecvError::Disconnected) => {
                         // The sender has been dropped, meaning the reader thread and process are finished.
+                        let stopped = self.stopping_since.take().is_some();
+                        self.run_ended(stopped);
                         self.is_running = false;
-                        self.line_receiver = None;
                         self.solver_process = None; // The Child process is dropped here, reaping it.
                         self.start_time = None;
+                        if !stopped && self.user_setup.notify_on_finish {
+                            self.notify_finished();
+                        }
                         break;
                     }
                 }
+                // A handler may have stopped the run, e.g. on divergence.
+                if !self.is_running {
+                    break;
+                }
+            }
+            if self.is_running {
+                self.line_receiver = Some(receiver);
             }
             ctx.request_repaint(); // Request a repaint to show new data
         }
 
+        // E.g. a subprocess outside the killed process group may still hold the output open.
+        if self
+            .stopping_since
+            .is_some_and(|since| since.elapsed() >= STOP_TIMEOUT)
+        {
+            self.push_output_line("Solver output did not close after stopping".to_string());
+            self.end_stopped_run();
+        }
+
+        if let (Some(start_time), Some(limit)) = (self.start_time, self.user_setup.max_runtime_secs)
+        {
+            if self.is_running
+                && self.stopping_since.is_none()
+                && start_time.elapsed() >= Duration::from_secs(limit)
+            {
+                self.push_output_line("Killed: exceeded max runtime".to_string());
+                self.stop_analysis();
+            }
+        }
+
+        // Start the next queued job once the previous one has ended.
+        if !self.is_running {
+            match self.job_queue.pop_front() {
+                Some(next) => {
+                    let request = self.run_request(next, false);
+                    self.start_analysis(request);
+                }
+                None => self.queue_total = 0,
+            }
+        }
+
+        if self.is_running {
+            self.poll_result_file_sizes();
+        }
+
         egui::TopBottomPanel::bottom("footer").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.hyperlink_to("GitHub", "https://github.com/calculix/ccx_runner");
+                ui.toggle_value(&mut self.show_about, "About");
                 egui::warn_if_debug_build(ui);
 
+                if self.is_running {
+                    if self.queue_total > 0 {
+                        ui.separator();
+                        ui.label(format!(
+                            "Queue {}/{}",
+                            self.queue_total - self.job_queue.len(),
+                            self.queue_total
+                        ));
+                    }
+                    for (name, size) in &self.result_file_sizes {
+                        ui.separator();
+                        ui.label(format!("{}: {}", name, format_bytes(*size)));
+                    }
+                }
+
+                // Right-to-left, so the options are listed in reverse.
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    egui::widgets::global_dark_light_mode_switch(ui);
+                    ui.selectable_value(&mut self.user_setup.theme, Theme::Dark, "🌙 Dark");
+                    ui.selectable_value(&mut self.user_setup.theme, Theme::Light, "☀ Light");
+                    ui.selectable_value(&mut self.user_setup.theme, Theme::System, "System");
                 });
             });
         });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Settings");
-            {
-                ui.label("Path to Calculix Binary");
-                ui.horizontal(|ui| {
-                    let mut ccx_path_str = self.user_setup.calculix_bin_path.display().to_string();
-                    let response = ui.add(
-                        egui::TextEdit::singleline(&mut ccx_path_str)
-                            .desired_width(ui.available_width() - 50.0),
+        // Above the footer, so the latest solver output stays visible on the other tabs.
+        if self.ansicht != Ansicht::SolverOutput {
+            if let Some((elapsed, line)) = self.solver_output_buffer.back() {
+                egui::TopBottomPanel::bottom("last_output").show(ctx, |ui| {
+                    ui.add(
+                        egui::Label::new(
+                            egui::RichText::new(format!("[{:>7.1}s] {}", elapsed, line.trim()))
+                                .monospace()
+                                .weak(),
+                        )
+                        .truncate(),
                     );
-                    if response.changed() {
-                        self.user_setup.calculix_bin_path = PathBuf::from(ccx_path_str);
-                    }
-
-                    if ui.button("…").clicked() {
-                        if let Some(path) = rfd::FileDialog::new().pick_file() {
-                            self.user_setup.calculix_bin_path = path;
-                        }
-                    }
                 });
             }
-            {
-                ui.label("Path to project directory");
-                ui.horizontal(|ui| {
-                    let mut project_dir_str =
-                        self.user_setup.project_dir_path.display().to_string();
-                    let response = ui.add(
-                        egui::TextEdit::singleline(&mut project_dir_str)
-                            .desired_width(ui.available_width() - 50.0),
-                    );
-                    if response.changed() {
-                        self.user_setup.project_dir_path = PathBuf::from(project_dir_str);
-                        self.refresh_inp_files();
-                    }
+        }
 
-                    if ui.button("…").clicked() {
-                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                            self.user_setup.project_dir_path = path;
-                            self.refresh_inp_files();
-                        }
+        // Ignored while typing, e.g. so Esc or Ctrl+R in the filter field don't act on the run.
+        let (run_shortcut, rerun_shortcut, mut stop_shortcut) = if ctx.wants_keyboard_input() {
+            (false, false, false)
+        } else {
+            ctx.input_mut(|i| {
+                for (key, ansicht) in TAB_SHORTCUTS {
+                    if i.consume_key(egui::Modifiers::COMMAND, key) {
+                        self.ansicht = ansicht;
                     }
-                });
-            }
+                }
+                // Before F5, which would also match Shift+F5.
+                let rerun = !self.is_running && i.consume_shortcut(&RERUN_SHORTCUT);
+                let run = !self.is_running
+                    && (i.consume_key(egui::Modifiers::NONE, egui::Key::F5)
+                        || i.consume_shortcut(&RUN_SHORTCUT));
+                let stop =
+                    self.is_running && i.consume_key(egui::Modifiers::NONE, egui::Key::Escape);
+                (run, rerun, stop)
+            })
+        };
 
-            if !self.is_running {
-                ui.horizontal(|ui| {
-                    let max_cores = default_num_cores();
-                    ui.label("Number of Cores:");
-                    ui.add(
-                        egui::DragValue::new(&mut self.user_setup.num_cores).range(1..=max_cores),
-                    );
-                });
-            }
+        // The run may have ended while the confirmation was open.
+        self.confirm_stop &= self.is_running && self.stopping_since.is_none();
+        if self.confirm_stop && stop_shortcut {
+            // Esc dismisses the confirmation rather than asking again.
+            self.confirm_stop = false;
+            stop_shortcut = false;
+        }
 
-            // Drop-down for .inp file
-            if !self.is_running {
-                let selected_file_name = self
-                    .selected_inp_file
-                    .as_ref()
-                    .and_then(|p| p.file_name())
-                    .and_then(|s| s.to_str())
-                    .map(|s| s.to_string())
-                    .unwrap_or_else(|| "Select a file".to_string());
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let ccx_error = crate::solver::validate_ccx_setup(&self.user_setup).err();
 
-                ui.label("Input file");
-                egui::ComboBox::from_id_source("inp_file_selector")
-                    .selected_text(selected_file_name)
-                    .show_ui(ui, |ui| {
-                        self.refresh_inp_files();
+            // Collapsed once configured, to leave room for the output and plots.
+            let configured = ccx_error.is_none() && self.project_dir_valid();
+            egui::CollapsingHeader::new(egui::RichText::new("Settings").heading())
+                .id_source("settings")
+                .default_open(!configured)
+                .show(ui, |ui| self.settings(ui, ccx_error.as_ref()));
 
-                        if self.available_inp_files.is_empty() {
-                            ui.label("No .inp files found.");
-                        } else {
-                            // Use a scroll area in case there are many files.
-                            egui::ScrollArea::vertical()
-                                .max_height(200.0)
-                                .show(ui, |ui| {
-                                    for f in &self.available_inp_files {
-                                        let file_name =
-                                            f.file_name().unwrap().to_str().unwrap().to_string();
-                                        ui.selectable_value(
-                                            &mut self.selected_inp_file,
-                                            Some(f.clone()),
-                                            file_name,
-                                        );
-                                    }
-                                });
-                        }
-                    });
+            if self.project_dir_valid() {
+                egui::CollapsingHeader::new(egui::RichText::new("Notes").heading())
+                    .id_source("notes")
+                    .show(ui, |ui| self.project_notes(ui));
             }
 
             ui.add_space(5.0);
 
             if self.is_running {
                 ui.horizontal(|ui| {
-                    if ui.button("Stop Analysis").clicked() {
-                        if let Some(process) = self.solver_process.take() {
-                            let mut process = process.lock().unwrap();
-                            match process.kill() {
-                                Ok(_) => {
-                                    println!("Process killed");
-                                }
-                                Err(e) => println!("Failed to kill process: {}", e),
-                            }
-                        }
-                        self.is_running = false;
-                        self.line_receiver = None;
-                        self.start_time = None;
+                    ui.add_enabled(false, egui::Button::new("Run Analysis"))
+                        .on_disabled_hover_text("An analysis is already running.");
+                    if self.stopping_since.is_some() {
+                        ui.add_enabled(false, egui::Button::new("Stopping…"));
+                    } else if ui.button("Stop Analysis").on_hover_text("Esc").clicked()
+                        || stop_shortcut
+                    {
+                        self.request_stop();
                     }
 
                     if let Some(start_time) = self.start_time {
+                        let stalled = self.last_line_time.unwrap_or(start_time).elapsed()
+                            >= OUTPUT_STALL_HINT;
+                        if stalled {
+                            ui.weak("waiting…")
+                                .on_hover_text("No new output in the last few seconds");
+                        } else {
+                            ui.spinner();
+                        }
                         let elapsed = start_time.elapsed();
                         ui.label(format!("Running for: {:.1}s", elapsed.as_secs_f32()));
+                        if let Some(remaining) = self
+                            .step_info
+                            .last()
+                            .and_then(|info| self.eta.remaining_secs(info))
+                        {
+                            ui.label(format!("ETA: ~{}", format_duration(remaining)));
+                        }
                         ctx.request_repaint();
                     }
                 });
-            } else if ui.button("Run Analysis").clicked() {
-                match config::save(&self.user_setup) {
-                    Ok(_) => {} // No-op
-                    Err(e) => panic!("{}", e),
-                }
-                if let Some(inp_path) = self.selected_inp_file.clone() {
-                    let job_name = inp_path.file_stem().unwrap().to_str().unwrap();
-                    let (sender, receiver) = mpsc::channel::<SolverMessage>();
-                    self.line_receiver = Some(receiver);
-                    self.is_running = true;
-                    self.start_time = Some(Instant::now());
-                    self.solver_output_buffer.clear();
-                    self.residual_data.clear();
-                    self.step_info.clear();
-
-                    let child = crate::solver::spawn_process(
-                        &self.user_setup.calculix_bin_path,
-                        &self.user_setup.project_dir_path,
-                        job_name,
-                        self.user_setup.num_cores,
+
+                // E.g. a wrapper script that blocks while waiting for a license.
+                let silent_for = self
+                    .last_line_time
+                    .or(self.start_time)
+                    .map(|since| since.elapsed());
+                if let Some(silent_for) = silent_for.filter(|silent_for| {
+                    self.stopping_since.is_none()
+                        && *silent_for >= Duration::from_secs(self.user_setup.silence_warning_secs)
+                }) {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!(
+                                "⚠ No output for {}. The solver may be stuck.",
+                                format_duration(silent_for.as_secs_f64())
+                            ),
+                        );
+                        if ui.button("Stop").clicked() {
+                            self.request_stop();
+                        }
+                    });
+                }
+                if self.run_dir_missing {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        "⚠ The job directory has disappeared. The results can't be written.",
                     );
+                }
+            } else {
+                let run_blocker = self.run_blocker();
+                let (run_clicked, rerun_clicked, check_clicked, queue_clicked, clean_clicked) = ui
+                    .horizontal(|ui| {
+                        let run_clicked = ui
+                            .add_enabled(run_blocker.is_none(), egui::Button::new("Run Analysis"))
+                            .on_hover_text(format!(
+                                "F5 or {}",
+                                ui.ctx().format_shortcut(&RUN_SHORTCUT)
+                            ))
+                            .on_disabled_hover_text(run_blocker.unwrap_or_default())
+                            .clicked()
+                            || (run_shortcut && self.can_run());
+                        let rerun_hint = match &self.last_run {
+                            Some(last_run) => format!(
+                                "Run {} again with the same settings ({})",
+                                relative_display(
+                                    &last_run.inp_path,
+                                    &self.user_setup.project_dir_path
+                                ),
+                                ui.ctx().format_shortcut(&RERUN_SHORTCUT)
+                            ),
+                            None => "Nothing has been run yet.".to_string(),
+                        };
+                        let rerun_clicked = ui
+                            .add_enabled(
+                                run_blocker.is_none() && self.last_run.is_some(),
+                                egui::Button::new("Re-run"),
+                            )
+                            .on_hover_text(rerun_hint.as_str())
+                            .on_disabled_hover_text(run_blocker.unwrap_or(rerun_hint.as_str()))
+                            .clicked()
+                            || (rerun_shortcut && self.can_run());
+                        let check_clicked = ui
+                            .add_enabled(run_blocker.is_none(), egui::Button::new("Check input"))
+                            .on_hover_text(
+                                "Reads the input deck and stops at the first step or error.",
+                            )
+                            .on_disabled_hover_text(run_blocker.unwrap_or_default())
+                            .clicked();
+                        let queue_clicked = ui
+                            .add_enabled(
+                                run_blocker.is_none() && !self.queue_selection.is_empty(),
+                                egui::Button::new(format!(
+                                    "Run Queue ({})",
+                                    self.queue_selection.len()
+                                )),
+                            )
+                            .on_disabled_hover_text(
+                                run_blocker.unwrap_or("Tick at least one file in the batch queue."),
+                            )
+                            .clicked();
+                        let clean_clicked = ui
+                            .add_enabled(
+                                self.selected_inp_file.is_some(),
+                                egui::Button::new("Clean…"),
+                            )
+                            .on_hover_text("Delete result files of earlier runs of this job")
+                            .clicked();
+                        (
+                            run_clicked,
+                            rerun_clicked,
+                            check_clicked,
+                            queue_clicked,
+                            clean_clicked,
+                        )
+                    })
+                    .inner;
+
+                if run_clicked || check_clicked {
+                    self.user_setup.last_inp_file = self.selected_inp_file.clone();
+                    self.save_config();
+                    if let Some(inp_path) = self.selected_inp_file.clone() {
+                        let request = self.run_request(inp_path, check_clicked);
+                        self.request_run(request);
+                    } else {
+                        self.push_output_line("No '.inp' file selected.".to_string());
+                    }
+                } else if rerun_clicked {
+                    self.rerun_last();
+                } else if queue_clicked {
+                    // Run in the order the files are listed, not the order they were ticked.
+                    self.job_queue = self
+                        .available_inp_files
+                        .iter()
+                        .filter(|f| self.queue_selection.contains(f))
+                        .cloned()
+                        .collect();
+                    self.queue_total = self.job_queue.len();
+                    if let Some(first) = self.job_queue.pop_front() {
+                        let request = self.run_request(first, false);
+                        self.start_analysis(request);
+                    }
+                } else if clean_clicked {
+                    if let Some(inp_path) = &self.selected_inp_file {
+                        let run_dir = crate::solver::results_dir(&self.user_setup, inp_path);
+                        let job_name = crate::solver::job_name(inp_path);
+                        self.clean_selection = Some(
+                            crate::solver::job_artifacts(run_dir, job_name)
+                                .into_iter()
+                                .map(|path| (path, true))
+                                .collect(),
+                        );
+                    }
+                }
+            }
+
+            if !self.is_running {
+                if let Some(artifacts) = &mut self.clean_selection {
+                    let project_dir = &self.user_setup.project_dir_path;
+                    let mut delete = false;
+                    let mut close = false;
+                    ui.group(|ui| {
+                        if artifacts.is_empty() {
+                            ui.label("No generated files found for this job.");
+                        } else {
+                            ui.label("Delete these generated files?");
+                            for (path, selected) in artifacts.iter_mut() {
+                                ui.checkbox(selected, relative_display(path, project_dir));
+                            }
+                        }
+                        ui.horizontal(|ui| {
+                            delete = ui
+                                .add_enabled(
+                                    artifacts.iter().any(|(_, selected)| *selected),
+                                    egui::Button::new("Delete selected"),
+                                )
+                                .clicked();
+                            close = ui.button("Cancel").clicked();
+                        });
+                    });
 
-                    match child {
-                        Ok(mut child) => {
-                            crate::solver::spawn_reader_thread(&mut child, sender);
-                            self.solver_process = Some(Arc::new(Mutex::new(child)));
+                    let mut errors = Vec::new();
+                    if delete {
+                        for (path, _) in artifacts.iter().filter(|(_, selected)| *selected) {
+                            if let Err(e) = fs::remove_file(path) {
+                                errors.push(format!("Failed to delete {}: {}", path.display(), e));
+                            }
                         }
-                        Err(e) => {
-                            self.solver_output_buffer
-                                .push(format!("Failed to start process: {}", e));
-                            self.is_running = false;
+                        close = true;
+                    }
+                    if close {
+                        self.clean_selection = None;
+                    }
+                    for error in errors {
+                        self.push_output_line(error);
+                    }
+                }
+            }
+
+            if !self.is_running && !self.missing_includes.is_empty() {
+                let mut run_anyway = false;
+                ui.group(|ui| {
+                    ui.colored_label(ui.visuals().warn_fg_color, "⚠ Missing *INCLUDE files:");
+                    for path in &self.missing_includes {
+                        ui.label(relative_display(path, &self.user_setup.project_dir_path));
+                    }
+                    ui.horizontal(|ui| {
+                        run_anyway = ui.button("Run anyway").clicked();
+                        if ui.button("Dismiss").clicked() {
+                            self.missing_includes.clear();
+                            self.include_prompt = None;
+                        }
+                    });
+                });
+                if run_anyway {
+                    self.missing_includes.clear();
+                    if let Some(request) = self.include_prompt.take() {
+                        self.start_unless_up_to_date(request);
+                    }
+                }
+            }
+
+            if !self.is_running {
+                let prompt_inp = self.overwrite_prompt.as_ref().map(|r| r.inp_path.clone());
+                if let Some(inp_path) = prompt_inp {
+                    let mut run_anyway = false;
+                    ui.group(|ui| {
+                        ui.label(format!(
+                            "ℹ The results of {} are up to date. Re-run anyway?",
+                            relative_display(&inp_path, &self.user_setup.project_dir_path)
+                        ));
+                        ui.horizontal(|ui| {
+                            run_anyway = ui.button("Run anyway").clicked();
+                            if ui.button("Cancel").clicked() {
+                                self.overwrite_prompt = None;
+                            }
+                        });
+                    });
+                    if run_anyway {
+                        if let Some(request) = self.overwrite_prompt.take() {
+                            self.start_analysis(request);
                         }
                     }
-                } else {
-                    self.solver_output_buffer
-                        .push("No '.inp' file selected.".to_string());
                 }
             }
 
+            if !self.is_running {
+                ui.horizontal(|ui| {
+                    self.completion_banner(ui);
+                    if self.run_succeeded() && ui.button("Open in CGX").clicked() {
+                        self.open_results_in_cgx();
+                    }
+                    if let (Some(dir), Some(job)) = (&self.current_run_dir, &self.current_job) {
+                        let frd_path = dir.join(format!("{}.frd", job));
+                        if frd_path.is_file()
+                            && ui
+                                .button("🗁 Reveal .frd")
+                                .on_hover_text(frd_path.display().to_string())
+                                .clicked()
+                        {
+                            let dir = dir.clone();
+                            self.open_externally(&dir);
+                        }
+                    }
+                });
+            }
+
             // Tabs
             ui.add_space(10.0);
+            let num_errors = self.num_errors();
+            let num_warnings = self.diagnostics.len() - num_errors;
+            let output_tab_label = if self.diagnostics.is_empty() {
+                egui::RichText::new("Solver Output")
+            } else {
+                let label = format!(
+                    "Solver Output ({} errors, {} warnings)",
+                    num_errors, num_warnings
+                );
+                if num_errors > 0 {
+                    egui::RichText::new(label).color(ui.visuals().error_fg_color)
+                } else {
+                    egui::RichText::new(label).color(ui.visuals().warn_fg_color)
+                }
+            };
+            let tab_hint = |index: usize| {
+                let (key, _) = TAB_SHORTCUTS[index];
+                ctx.format_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, key))
+            };
             ui.horizontal(|ui| {
-                ui.selectable_value(&mut self.ansicht, Ansicht::SolverOutput, "Solver Output");
-                ui.selectable_value(&mut self.ansicht, Ansicht::Overview, "Overview");
+                ui.selectable_value(&mut self.ansicht, Ansicht::SolverOutput, output_tab_label)
+                    .on_hover_text(tab_hint(0));
+                ui.selectable_value(&mut self.ansicht, Ansicht::Overview, "Overview")
+                    .on_hover_text(tab_hint(1));
+                ui.selectable_value(
+                    &mut self.ansicht,
+                    Ansicht::Problems,
+                    format!("Problems ({})", self.diagnostics.len()),
+                )
+                .on_hover_text(tab_hint(2));
+                ui.selectable_value(&mut self.ansicht, Ansicht::History, "History")
+                    .on_hover_text(tab_hint(3));
             });
             ui.separator();
 
             match self.ansicht {
                 Ansicht::SolverOutput => {
-                    ui.heading("Solver Output");
+                    ui.horizontal(|ui| {
+                        ui.heading("Solver Output");
+                        ui.checkbox(
+                            &mut self.show_stderr_pane,
+                            format!("Separate stderr pane ({})", self.stderr_buffer.len()),
+                        );
+                        // Clearing is allowed mid-run; new lines keep appending afterwards.
+                        if ui.button("Clear").clicked() {
+                            self.solver_output_buffer.clear();
+                            self.truncated_lines = 0;
+                            self.step_starts.clear();
+                            self.stderr_buffer.clear();
+                            self.diagnostics.clear();
+                            self.residual_data.clear();
+                            self.increment_timings.clear();
+                            self.increment_iterations.clear();
+                            self.step_info.clear();
+                            self.selected_rows = None;
+                            self.expanded_rows.clear();
+                        }
+                        ui.checkbox(&mut self.show_timestamps, "Timestamps");
+                        ui.checkbox(&mut self.group_by_step, "Group by step");
+                        if ui.checkbox(&mut self.auto_scroll, "Auto-scroll").changed()
+                            && self.auto_scroll
+                        {
+                            self.scroll_to_bottom = true;
+                        }
+                        ui.label("Font size:");
+                        ui.add(
+                            egui::DragValue::new(&mut self.user_setup.output_font_size)
+                                .range(6.0..=48.0)
+                                .speed(0.25)
+                                .suffix(" pt"),
+                        );
+                        // Large logs render much faster in an external editor.
+                        if let Some(log_path) =
+                            self.current_log_path.clone().filter(|path| path.is_file())
+                        {
+                            if ui
+                                .button("Open log")
+                                .on_hover_text(log_path.display().to_string())
+                                .clicked()
+                            {
+                                self.open_externally(&log_path);
+                            }
+                        }
+                    });
 
-                    let hint =
-                        "Filter with AND (&) and OR (|). E.g. 'force & iteration | convergence'";
-                    ui.add(
-                        egui::TextEdit::singleline(&mut self.filter_query)
-                            .hint_text(hint)
-                            .desired_width(f32::INFINITY),
-                    );
+                    let hint = if self.regex_filter {
+                        "Regular expression, e.g. '^ iteration \\d+' or '(?i)error'"
+                    } else {
+                        "Filter with AND (&), OR (|), NOT (!). E.g. 'iteration & !convergence'"
+                    };
+                    ui.horizontal(|ui| {
+                        for (label, preset) in FILTER_PRESETS {
+                            let active = !self.regex_filter
+                                && crate::filter::contains_clauses(
+                                    &self.user_setup.last_filter,
+                                    preset,
+                                );
+                            if ui
+                                .selectable_label(active, label)
+                                .on_hover_text(preset)
+                                .clicked()
+                            {
+                                if self.regex_filter {
+                                    // A regex can't be combined with the presets.
+                                    self.regex_filter = false;
+                                    self.user_setup.last_filter.clear();
+                                }
+                                self.user_setup.last_filter = crate::filter::toggle_clauses(
+                                    &self.user_setup.last_filter,
+                                    preset,
+                                );
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.user_setup.last_filter)
+                                .hint_text(hint)
+                                .desired_width(ui.available_width() - 70.0),
+                        );
+                        ui.checkbox(&mut self.regex_filter, "regex");
+                        self.saved_filters_menu(ui);
+                    });
+
+                    let mut active_regex = None;
+                    if self.regex_filter {
+                        let is_cached = matches!(
+                            &self.filter_regex,
+                            Some((query, _)) if *query == self.user_setup.last_filter
+                        );
+                        if !is_cached {
+                            let compiled = regex::Regex::new(&self.user_setup.last_filter);
+                            self.filter_regex =
+                                Some((self.user_setup.last_filter.clone(), compiled));
+                        }
+                        match &self.filter_regex {
+                            Some((_, Ok(regex))) => active_regex = Some(regex),
+                            Some((_, Err(e))) => {
+                                // Show everything until the pattern is fixed.
+                                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                            }
+                            None => {}
+                        }
+                    }
+
+                    if self.truncated_lines > 0 {
+                        ui.weak(format!(
+                            "(truncated: {} earlier lines dropped)",
+                            self.truncated_lines
+                        ));
+                    }
 
-                    let query = self.filter_query.trim();
-                    let filtered_lines: Vec<_> = if query.is_empty() {
-                        self.solver_output_buffer.iter().collect()
+                    let filter = if self.regex_filter {
+                        crate::filter::Filter::default()
                     } else {
-                        // DNF parsing: OR of ANDs
-                        // "a & b | c" -> OR clauses: [["a", "b"], ["c"]]
-                        let or_clauses: Vec<Vec<String>> = query
-                            .split('|')
-                            .map(|or_part| {
-                                or_part
-                                    .split('&')
-                                    .map(|s| s.trim().to_lowercase())
-                                    .filter(|s| !s.is_empty())
-                                    .collect()
-                            })
-                            .filter(|and_terms: &Vec<String>| !and_terms.is_empty())
-                            .collect();
+                        crate::filter::compile(&self.user_setup.last_filter)
+                    };
+                    let passes_filter = |line: &str| match active_regex {
+                        Some(regex) => regex.is_match(line),
+                        None => filter.is_empty() || filter.matches(line),
+                    };
+                    // With their line numbers, which key the selection and expanded lines.
+                    let filtered_lines: Vec<(usize, f32, &str)> = self
+                        .solver_output_buffer
+                        .iter()
+                        .enumerate()
+                        .map(|(k, (elapsed, line))| {
+                            (self.truncated_lines + k, *elapsed, line.as_str())
+                        })
+                        .filter(|(_, _, line)| passes_filter(line))
+                        .collect();
+                    let show_timestamps = self.show_timestamps;
+                    let display_line = |&(_, elapsed, line): &(usize, f32, &str)| -> String {
+                        if show_timestamps {
+                            format!("[{:>7.1}s] {}", elapsed, line)
+                        } else {
+                            line.to_string()
+                        }
+                    };
 
-                        self.solver_output_buffer
+                    let find_term = self.find_query.trim().to_ascii_lowercase();
+                    // Line numbers of the matches, in ascending order.
+                    let find_matches: Vec<usize> = if find_term.is_empty() {
+                        Vec::new()
+                    } else {
+                        filtered_lines
                             .iter()
-                            .filter(|line| {
-                                let lower_line = line.to_lowercase();
-                                // A line matches if it matches ANY of the OR clauses
-                                or_clauses.iter().any(|and_terms| {
-                                    // An OR clause matches if the line contains ALL of its AND terms
-                                    and_terms.iter().all(|term| lower_line.contains(term))
-                                })
-                            })
+                            .filter(|(_, _, line)| line.to_ascii_lowercase().contains(&find_term))
+                            .map(|&(number, _, _)| number)
                             .collect()
                     };
+                    ui.horizontal(|ui| {
+                        ui.label("Find:");
+                        if ui
+                            .add(
+                                egui::TextEdit::singleline(&mut self.find_query)
+                                    .desired_width(200.0),
+                            )
+                            .changed()
+                        {
+                            self.find_row = None;
+                        }
+                        let current = self.find_row.unwrap_or(usize::MAX);
+                        if ui.button("Prev").clicked() {
+                            self.find_row = find_matches
+                                .iter()
+                                .rev()
+                                .find(|&&row| row < current)
+                                .or(find_matches.last())
+                                .copied();
+                            self.scroll_to_find = true;
+                        }
+                        if ui.button("Next").clicked() {
+                            self.find_row = find_matches
+                                .iter()
+                                .find(|&&row| row > current)
+                                .or(find_matches.first())
+                                .copied();
+                            self.scroll_to_find = true;
+                        }
+                        if !find_term.is_empty() {
+                            let position = self
+                                .find_row
+                                .and_then(|row| find_matches.iter().position(|&m| m == row))
+                                .map_or("-".to_string(), |p| (p + 1).to_string());
+                            ui.label(format!("{}/{} matches", position, find_matches.len()));
+                        }
+                    });
 
-                    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
-                    let num_rows = filtered_lines.len();
+                    let selected_range = self
+                        .selected_rows
+                        .map(|(anchor, end)| anchor.min(end)..=anchor.max(end));
+                    ui.horizontal(|ui| {
+                        // Copies only what passes the filter, i.e. what is visible.
+                        if ui.button("Copy all").clicked() {
+                            let text = filtered_lines
+                                .iter()
+                                .map(display_line)
+                                .collect::<Vec<_>>()
+                                .join("\n");
+                            ui.output_mut(|o| o.copied_text = text);
+                        }
+                        if let Some(range) = selected_range.clone() {
+                            let selection: Vec<String> = filtered_lines
+                                .iter()
+                                .filter(|(number, _, _)| range.contains(number))
+                                .map(display_line)
+                                .collect();
+                            if ui
+                                .button(format!("Copy selection ({} lines)", selection.len()))
+                                .clicked()
+                            {
+                                ui.output_mut(|o| o.copied_text = selection.join("\n"));
+                            }
+                            if ui.button("Deselect").clicked() {
+                                self.selected_rows = None;
+                            }
+                        } else {
+                            ui.weak(
+                                "Click a line to select it, shift-click to extend the selection.",
+                            );
+                        }
+                    });
 
-                    egui::ScrollArea::both()
-                        .auto_shrink([false, false])
-                        .stick_to_bottom(true)
-                        .show_rows(ui, row_height, num_rows, |ui, row_range| {
-                            for i in row_range {
-                                if let Some(line) = filtered_lines.get(i) {
-                                    ui.label(egui::RichText::new(*line).monospace());
+                    // Everything below uses the configured size; `show_rows` picks it up via
+                    // `text_style_height`, so virtual scrolling stays in step.
+                    ui.style_mut().text_styles.insert(
+                        egui::TextStyle::Monospace,
+                        egui::FontId::monospace(self.user_setup.output_font_size),
+                    );
+
+                    if self.show_stderr_pane {
+                        ui.label("stderr");
+                        let error_color = ui.visuals().error_fg_color;
+                        egui::ScrollArea::both()
+                            .id_source("stderr_scroll")
+                            .max_height(150.0)
+                            .auto_shrink([false, true])
+                            .stick_to_bottom(true)
+                            .show(ui, |ui| {
+                                for line in &self.stderr_buffer {
+                                    ui.label(
+                                        egui::RichText::new(line).monospace().color(error_color),
+                                    );
+                                }
+                            });
+                        ui.separator();
+                    }
+
+                    if self.group_by_step {
+                        let error_color = ui.visuals().error_fg_color;
+                        let warn_color = ui.visuals().warn_fg_color;
+                        let text_color = ui.visuals().text_color();
+                        let mut sections = vec![(None, 0)];
+                        sections.extend(self.step_starts.iter().map(|&(step, start)| {
+                            (Some(step), start.saturating_sub(self.truncated_lines))
+                        }));
+                        let num_sections = sections.len();
+                        // Sections are laid out in full, so finished steps are best kept collapsed.
+                        egui::ScrollArea::both()
+                            .id_source("grouped_output_scroll")
+                            .auto_shrink([false, false])
+                            .show(ui, |ui| {
+                                for (k, &(step, start)) in sections.iter().enumerate() {
+                                    let end = sections
+                                        .get(k + 1)
+                                        .map_or(self.solver_output_buffer.len(), |&(_, s)| s);
+                                    let lines: Vec<(usize, f32, &str)> = (start.min(end)..end)
+                                        .map(|j| {
+                                            let (elapsed, line) = &self.solver_output_buffer[j];
+                                            (self.truncated_lines + j, *elapsed, line.as_str())
+                                        })
+                                        .filter(|(_, _, line)| passes_filter(line))
+                                        .collect();
+                                    let title = match step {
+                                        Some(step) => format!("Step {}", step),
+                                        None if lines.is_empty() => continue,
+                                        None => "Before the first step".to_string(),
+                                    };
+                                    // Keyed by position, so the open state survives new lines.
+                                    egui::CollapsingHeader::new(format!(
+                                        "{} ({} lines)",
+                                        title,
+                                        lines.len()
+                                    ))
+                                    .id_source(("output_step", k))
+                                    .default_open(k + 1 == num_sections)
+                                    .show(ui, |ui| {
+                                        for entry in &lines {
+                                            let color = match diagnostic_severity(entry.2) {
+                                                Some(Severity::Error) => error_color,
+                                                Some(Severity::Warning) => warn_color,
+                                                None => text_color,
+                                            };
+                                            ui.label(
+                                                egui::RichText::new(display_line(entry))
+                                                    .monospace()
+                                                    .color(color),
+                                            );
+                                        }
+                                    });
+                                }
+                            });
+                    } else {
+                        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                        let error_color = ui.visuals().error_fg_color;
+                        let warn_color = ui.visuals().warn_fg_color;
+                        let text_color = ui.visuals().text_color();
+                        let highlight_color = ui.visuals().selection.bg_fill;
+                        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                        let num_rows = filtered_lines.len();
+
+                        let row_height_with_spacing = row_height + ui.spacing().item_spacing.y;
+                        let mut scroll_area = egui::ScrollArea::both()
+                            .auto_shrink([false, false])
+                            .stick_to_bottom(self.auto_scroll && self.find_row.is_none());
+                        let find_index = self.find_row.and_then(|number| {
+                            filtered_lines
+                                .binary_search_by_key(&number, |&(number, _, _)| number)
+                                .ok()
+                        });
+                        if let Some(row) = find_index.filter(|_| self.scroll_to_find) {
+                            let offset =
+                                row as f32 * row_height_with_spacing - ui.available_height() / 2.0;
+                            scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+                            self.scroll_to_find = false;
+                        } else if self.scroll_to_bottom {
+                            // The offset is clamped to the content, so overshooting is fine.
+                            scroll_area = scroll_area
+                                .vertical_scroll_offset(num_rows as f32 * row_height_with_spacing);
+                            self.scroll_to_bottom = false;
+                        }
+
+                        let selection_color = highlight_color.gamma_multiply(0.5);
+                        let output =
+                            scroll_area.show_rows(ui, row_height, num_rows, |ui, row_range| {
+                                for i in row_range {
+                                    if let Some(entry) = filtered_lines.get(i) {
+                                        let number = entry.0;
+                                        let color = match diagnostic_severity(entry.2) {
+                                            Some(Severity::Error) => error_color,
+                                            Some(Severity::Warning) => warn_color,
+                                            None => text_color,
+                                        };
+                                        let mut line = display_line(entry);
+                                        // Laying out huge lines, e.g. dumped arrays, stalls
+                                        // the viewer.
+                                        let cut = line
+                                            .char_indices()
+                                            .nth(LONG_LINE_CHARS)
+                                            .filter(|_| !self.expanded_rows.contains(&number));
+                                        if let Some((cut, _)) = cut {
+                                            line.truncate(cut);
+                                        }
+                                        let text: egui::WidgetText =
+                                            if find_matches.binary_search(&number).is_ok() {
+                                                highlighted_line(
+                                                    &line,
+                                                    &find_term,
+                                                    font_id.clone(),
+                                                    color,
+                                                    highlight_color,
+                                                )
+                                                .into()
+                                            } else {
+                                                egui::RichText::new(line)
+                                                    .monospace()
+                                                    .color(color)
+                                                    .into()
+                                            };
+                                        // Reserve a slot behind the text for the selection
+                                        // background.
+                                        let background = ui.painter().add(egui::Shape::Noop);
+                                        let label =
+                                            egui::Label::new(text).sense(egui::Sense::click());
+                                        let response = if cut.is_some() {
+                                            ui.horizontal(|ui| {
+                                                let response = ui.add(label);
+                                                if ui.link("…(show full)").clicked() {
+                                                    self.expanded_rows.insert(number);
+                                                }
+                                                response
+                                            })
+                                            .inner
+                                        } else {
+                                            ui.add(label)
+                                        };
+                                        if response.clicked() {
+                                            let shift = ui.input(|input| input.modifiers.shift);
+                                            self.selected_rows = match self.selected_rows {
+                                                Some((anchor, _)) if shift => {
+                                                    Some((anchor, number))
+                                                }
+                                                _ => Some((number, number)),
+                                            };
+                                        }
+                                        if selected_range
+                                            .as_ref()
+                                            .is_some_and(|r| r.contains(&number))
+                                        {
+                                            ui.painter().set(
+                                                background,
+                                                egui::Shape::rect_filled(
+                                                    response.rect,
+                                                    0.0,
+                                                    selection_color,
+                                                ),
+                                            );
+                                        }
+                                    }
                                 }
+                            });
+
+                        // Scrolling away from the bottom pauses auto-scroll, scrolling back
+                        // resumes it.
+                        if self.find_row.is_none() {
+                            let max_offset =
+                                (output.content_size.y - output.inner_rect.height()).max(0.0);
+                            let at_bottom = output.state.offset.y >= max_offset - row_height;
+                            if at_bottom != self.output_at_bottom {
+                                self.auto_scroll = at_bottom;
+                            }
+                            self.output_at_bottom = at_bottom;
+                        }
+
+                        if !self.auto_scroll && self.find_row.is_none() && num_rows > 0 {
+                            let corner = output.inner_rect.right_bottom();
+                            let button_rect = egui::Rect::from_min_max(
+                                corner - egui::vec2(150.0, 40.0),
+                                corner - egui::vec2(20.0, 14.0),
+                            );
+                            if ui
+                                .put(button_rect, egui::Button::new("Jump to latest ↓"))
+                                .clicked()
+                            {
+                                self.auto_scroll = true;
+                                self.scroll_to_bottom = true;
                             }
+                        }
+                    }
+                }
+
+                Ansicht::Problems => {
+                    if self.diagnostics.is_empty() {
+                        ui.label("No errors or warnings so far.");
+                    }
+                    let error_color = ui.visuals().error_fg_color;
+                    let warn_color = ui.visuals().warn_fg_color;
+                    egui::ScrollArea::vertical()
+                        .id_source("problems_scroll")
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+                                let color = match diagnostic.severity {
+                                    Severity::Error => error_color,
+                                    Severity::Warning => warn_color,
+                                };
+                                egui::CollapsingHeader::new(
+                                    egui::RichText::new(&diagnostic.message)
+                                        .monospace()
+                                        .color(color),
+                                )
+                                .id_source(("problem", i))
+                                .show(ui, |ui| {
+                                    for line in &diagnostic.detail {
+                                        ui.monospace(line);
+                                    }
+                                    let numbers = diagnostic.numbers().join(" ");
+                                    if !numbers.is_empty()
+                                        && ui
+                                            .button(format!("Copy numbers: {}", numbers))
+                                            .on_hover_text("E.g. element or node ids")
+                                            .clicked()
+                                    {
+                                        ui.output_mut(|o| o.copied_text = numbers);
+                                    }
+                                });
+                            }
+                        });
+                }
+
+                Ansicht::History => {
+                    let mut reselect = None;
+                    ui.horizontal(|ui| {
+                        ui.heading("Run History");
+                        if ui.button("Clear").clicked() {
+                            self.run_history.clear();
+                            self.config_saver.request_history(Vec::new());
+                        }
+                    });
+                    let now = SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs());
+                    egui::ScrollArea::vertical()
+                        .id_source("history_scroll")
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            egui::Grid::new("history_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("Job");
+                                    ui.label("Started");
+                                    ui.label("Duration");
+                                    ui.label("Outcome");
+                                    ui.end_row();
+
+                                    for (i, record) in self.run_history.iter().enumerate() {
+                                        ui.label(&record.job)
+                                            .on_hover_text(record.inp_file.display().to_string());
+                                        let age = now.saturating_sub(record.started);
+                                        ui.label(format!("{} ago", format_age(age)));
+                                        ui.label(format_duration(record.duration_secs));
+                                        match record.outcome {
+                                            RunOutcome::Succeeded => {
+                                                ui.colored_label(SUCCESS_COLOR, "Succeeded")
+                                            }
+                                            RunOutcome::Failed => ui.colored_label(
+                                                ui.visuals().error_fg_color,
+                                                "Failed",
+                                            ),
+                                            RunOutcome::Stopped => ui.colored_label(
+                                                ui.visuals().warn_fg_color,
+                                                "Stopped",
+                                            ),
+                                        };
+                                        if ui
+                                            .add_enabled(
+                                                !self.is_running,
+                                                egui::Button::new("Select"),
+                                            )
+                                            .clicked()
+                                        {
+                                            reselect = Some(i);
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
                         });
+                    if let Some(record) = reselect.and_then(|i| self.run_history.get(i)) {
+                        let inp_file = record.inp_file.clone();
+                        if let Some(dir) = inp_file.parent() {
+                            self.user_setup.project_dir_path = dir.to_path_buf();
+                        }
+                        self.selected_inp_file = Some(inp_file);
+                        self.refresh_inp_files();
+                    }
                 }
 
                 Ansicht::Overview => {
-                    ui.heading("Residual Plot");
-                    let points: PlotPoints = self
-                        .residual_data
-                        .iter()
-                        .map(|d| [d.total_iteration as f64, d.residual])
-                        .collect();
-                    let line = Line::new(points);
+                    let has_memory =
+                        self.memory_needed_mb.is_some() || self.memory_used_mb.is_some();
+                    if self.num_equations.is_some() || has_memory {
+                        ui.horizontal(|ui| {
+                            if let Some(equations) = self.num_equations {
+                                ui.strong(format!("Equations: {}", equations));
+                            }
+                            if let Some(nonzeros) = self.num_nonzeros {
+                                ui.separator();
+                                // Value plus row index per entry; factorizing needs a multiple.
+                                let matrix_bytes = nonzeros * 12;
+                                ui.label(format!(
+                                    "Nonzeros: {} (matrix ≈ {})",
+                                    nonzeros,
+                                    format_bytes(matrix_bytes)
+                                ));
+                            }
+                            let mb_to_bytes = |mb: f64| (mb * 1024.0 * 1024.0) as u64;
+                            if let Some(needed) = self.memory_needed_mb {
+                                ui.separator();
+                                ui.label(format!(
+                                    "Peak memory needed: {}",
+                                    format_bytes(mb_to_bytes(needed))
+                                ));
+                            }
+                            if let Some(used) = self.memory_used_mb {
+                                ui.separator();
+                                ui.label(format!(
+                                    "Peak memory used: {}",
+                                    format_bytes(mb_to_bytes(used))
+                                ));
+                            }
+                        });
+                        ui.add_space(10.0);
+                    }
+                    if let Some(info) = self.step_info.last() {
+                        if let Some(period) = info.step_period.filter(|p| *p > 0.0) {
+                            let progress = (info.step_time / period).clamp(0.0, 1.0);
+                            ui.add(egui::ProgressBar::new(progress as f32).text(format!(
+                                "Step {}: {:.4e} / {:.4e} ({:.0}%)",
+                                info.step,
+                                info.step_time,
+                                period,
+                                progress * 100.0
+                            )));
+                            ui.add_space(10.0);
+                        }
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.heading("Residual Plot");
+                        ui.checkbox(&mut self.residual_log_scale, "Log scale");
+                        if self.residual_data.iter().any(|d| d.correction.is_some()) {
+                            ui.checkbox(&mut self.show_corrections, "Corrections");
+                        }
+                        let mut show_target = self.user_setup.residual_target.is_some();
+                        if ui.checkbox(&mut show_target, "Target").changed() {
+                            self.user_setup.residual_target =
+                                show_target.then_some(DEFAULT_RESIDUAL_TARGET);
+                        }
+                        if let Some(target) = &mut self.user_setup.residual_target {
+                            // Proportional, since tolerances span orders of magnitude.
+                            let speed = (*target * 0.01).max(1e-12);
+                            ui.add(
+                                egui::DragValue::new(target)
+                                    .range(0.0..=f64::MAX)
+                                    .speed(speed)
+                                    .custom_formatter(|value, _| format!("{:.3e}", value))
+                                    .custom_parser(|text| text.trim().parse().ok()),
+                            );
+                        }
+                    });
+                    let log_scale = self.residual_log_scale;
+                    let target = self
+                        .user_setup
+                        .residual_target
+                        .filter(|target| !log_scale || *target > 0.0)
+                        .map(|target| if log_scale { target.log10() } else { target });
+                    // One series per residual kind and increment attempt. They share their
+                    // step's name and color, so each step can still be toggled via the legend.
+                    let mut series: BTreeMap<ResidualKey, Vec<[f64; 2]>> = BTreeMap::new();
+                    for d in &self.residual_data {
+                        let correction = d.correction.filter(|_| self.show_corrections);
+                        let values = std::iter::once((false, d.residual))
+                            .chain(correction.map(|correction| (true, correction)));
+                        for (is_correction, value) in values {
+                            // Non-positive values have no logarithm, so skip them in log mode.
+                            if log_scale && value <= 0.0 {
+                                continue;
+                            }
+                            let y = if log_scale { value.log10() } else { value };
+                            series
+                                .entry(ResidualKey {
+                                    kind: d.kind,
+                                    is_correction,
+                                    step: d.step,
+                                    increment: d.increment,
+                                    attempt: d.attempt,
+                                })
+                                .or_default()
+                                .push([d.total_iteration as f64, y]);
+                        }
+                    }
 
                     Plot::new("residual_plot")
                         .height(250.0)
                         .legend(egui_plot::Legend::default())
                         .x_axis_label("Total Iterations")
+                        .y_axis_label(if log_scale {
+                            "log10(Residual)"
+                        } else {
+                            "Residual"
+                        })
                         .show(ui, |plot_ui| {
-                            plot_ui.line(line.name("Largest Residual"));
+                            for (key, points) in series {
+                                let ResidualKey {
+                                    kind,
+                                    is_correction,
+                                    step,
+                                    ..
+                                } = key;
+                                let style = match kind {
+                                    _ if is_correction => LineStyle::dotted_dense(),
+                                    ResidualKind::Force => LineStyle::Solid,
+                                    ResidualKind::Flux => LineStyle::dashed_dense(),
+                                };
+                                let name = if is_correction {
+                                    format!("Step {} {} correction", step, kind.label())
+                                } else {
+                                    format!("Step {} {}", step, kind.label())
+                                };
+                                plot_ui.line(
+                                    Line::new(PlotPoints::from(points))
+                                        .color(step_color(step))
+                                        .style(style)
+                                        .name(name),
+                                );
+                            }
+                            if let Some(target) = target {
+                                plot_ui.hline(
+                                    HLine::new(target)
+                                        .style(LineStyle::dashed_loose())
+                                        .name("Target"),
+                                );
+                            }
+                        });
+
+                    if !self.increment_timings.is_empty() {
+                        ui.add_space(10.0);
+                        ui.heading("Increment Duration");
+                        // Increment numbers restart every step, so bars are placed in arrival order.
+                        let bars = self
+                            .increment_timings
+                            .iter()
+                            .enumerate()
+                            .map(|(i, (increment, secs))| {
+                                Bar::new(i as f64, *secs).name(format!("Increment {}", increment))
+                            })
+                            .collect();
+                        Plot::new("increment_timing_plot")
+                            .height(150.0)
+                            .x_axis_label("Increment (in order)")
+                            .y_axis_label("Seconds")
+                            .show(ui, |plot_ui| plot_ui.bar_chart(BarChart::new(bars)));
+                    }
+
+                    if !self.increment_iterations.is_empty() {
+                        ui.add_space(10.0);
+                        ui.heading("Iterations per Increment");
+                        // Colored like the residual plot, so hard steps stand out.
+                        let bars = self
+                            .increment_iterations
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &(step, increment, iterations))| {
+                                Bar::new(i as f64, iterations as f64)
+                                    .fill(step_color(step))
+                                    .name(format!("Step {}, increment {}", step, increment))
+                            })
+                            .collect();
+                        Plot::new("increment_iterations_plot")
+                            .height(150.0)
+                            .x_axis_label("Increment (in order)")
+                            .y_axis_label("Iterations")
+                            .show(ui, |plot_ui| plot_ui.bar_chart(BarChart::new(bars)));
+                    }
+
+                    if !self.solver_timings.is_empty() {
+                        ui.add_space(10.0);
+                        ui.heading("Solver Timing");
+                        egui::Grid::new("timing_grid").striped(true).show(ui, |ui| {
+                            ui.label("Phase");
+                            ui.label("Seconds");
+                            ui.end_row();
+                            for (phase, secs) in &self.solver_timings {
+                                ui.label(phase);
+                                ui.label(format!("{:.3}", secs));
+                                ui.end_row();
+                            }
+                            if let Some(wall) = self.wall_time_secs {
+                                ui.label("Wall time");
+                                ui.label(format!("{:.3}", wall));
+                                ui.end_row();
+                            }
                         });
+                        let total_cpu = self
+                            .solver_timings
+                            .iter()
+                            .find(|(phase, _)| phase.to_lowercase().contains("total"))
+                            .map(|(_, secs)| *secs);
+                        if let (Some(cpu), Some(wall)) = (total_cpu, self.wall_time_secs) {
+                            // CPU time is summed over threads, so this approximates the busy cores.
+                            ui.label(format!(
+                                "Total / wall time: {:.2} with {} cores",
+                                cpu / wall.max(f64::EPSILON),
+                                self.user_setup.cores()
+                            ));
+                        }
+                    }
 
                     ui.add_space(10.0);
 
                     // Step Table
-                    ui.heading("Step Information");
+                    ui.horizontal(|ui| {
+                        ui.heading("Step Information");
+                        let has_steps = !self.step_info.is_empty();
+                        if ui
+                            .add_enabled(has_steps, egui::Button::new("Export CSV…"))
+                            .clicked()
+                        {
+                            self.export_step_table("csv", steps_to_csv(&self.step_info));
+                        }
+                        if ui
+                            .add_enabled(has_steps, egui::Button::new("Export Markdown…"))
+                            .clicked()
+                        {
+                            self.export_step_table("md", steps_to_markdown(&self.step_info));
+                        }
+                    });
                     egui::Grid::new("step_grid").striped(true).show(ui, |ui| {
                         ui.label("Step");
                         ui.label("Increment");
                         ui.label("Attempt");
                         ui.label("Iterations");
                         ui.label("Total Time");
+                        ui.label("Δt");
+                        ui.label("Cutbacks");
+                        ui.label("Method");
                         ui.end_row();
 
-                        for data in &self.step_info {
-                            ui.label(data.step.to_string());
-                            ui.label(data.increment.to_string());
-                            ui.label(data.attempt.to_string());
-                            ui.label(data.iterations.to_string());
-                            ui.label(format!("{:.4e}", data.total_time));
+                        // A failed run is blamed on the step it ended in.
+                        let run_failed =
+                            !self.is_running && self.exit_status.is_some() && !self.run_succeeded();
+                        let num_steps = self.step_info.len();
+                        for (i, data) in self.step_info.iter().enumerate() {
+                            let troubled = data.cutbacks > 0 || (run_failed && i + 1 == num_steps);
+                            let health = if troubled {
+                                ui.visuals().error_fg_color
+                            } else if data.iterations > MANY_ITERATIONS {
+                                ui.visuals().warn_fg_color
+                            } else {
+                                SUCCESS_COLOR
+                            };
+                            let tint = health.gamma_multiply(0.25);
+                            let cell =
+                                |text: String| egui::RichText::new(text).background_color(tint);
+                            ui.label(cell(data.step.to_string()));
+                            ui.label(cell(data.increment.to_string()));
+                            ui.label(cell(data.attempt.to_string()));
+                            ui.label(cell(data.iterations.to_string()));
+                            ui.label(cell(format!("{:.4e}", data.total_time)));
+                            ui.label(cell(format!("{:.4e}", data.dtime)));
+                            ui.label(cell(data.cutbacks.to_string()));
+                            ui.label(cell(data.method.clone().unwrap_or_default()));
                             ui.end_row();
                         }
                     });
                 }
             }
         });
+
+        if self.show_about {
+            // What a bug report needs to know about this installation.
+            let about = [
+                ("Version", env!("CARGO_PKG_VERSION").to_string()),
+                ("Config file", config::config_path().display().to_string()),
+                (
+                    "CalculiX",
+                    self.ccx_version
+                        .clone()
+                        .unwrap_or_else(|| "unknown".to_string()),
+                ),
+                (
+                    "OS",
+                    format!("{} ({})", std::env::consts::OS, std::env::consts::ARCH),
+                ),
+                (
+                    "Cores",
+                    format!(
+                        "{} physical, {} logical",
+                        self.physical_cores,
+                        default_num_cores()
+                    ),
+                ),
+            ];
+            egui::Window::new("About")
+                .open(&mut self.show_about)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    egui::Grid::new("about_grid").show(ui, |ui| {
+                        for (label, value) in &about {
+                            ui.label(*label);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+                    if ui.button("Copy").clicked() {
+                        let text = about
+                            .iter()
+                            .map(|(label, value)| format!("{}: {}", label, value))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ui.output_mut(|o| o.copied_text = text);
+                    }
+                });
+        }
+
+        if self.confirm_stop {
+            let elapsed = self
+                .start_time
+                .map_or(0.0, |start_time| start_time.elapsed().as_secs_f64());
+            let (mut stop, mut keep_running) = (false, false);
+            egui::Window::new("Stop the analysis?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!("Really stop after {}?", format_duration(elapsed)));
+                    if !self.job_queue.is_empty() {
+                        ui.label("The queued jobs are dropped as well.");
+                    }
+                    ui.horizontal(|ui| {
+                        stop = ui.button("Stop").clicked();
+                        keep_running = ui.button("Keep running").clicked();
+                    });
+                });
+            if stop {
+                self.abort_run();
+            } else if keep_running {
+                self.confirm_stop = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_age, Ansicht, MainApp};
+    use crate::config::{ConfigSaver, Profiles};
+    use crate::solver::{ResidualData, ResidualKind, SolverMessage, StepInfo};
+    use std::time::Instant;
+
+    #[test]
+    fn collects_overview_data_on_the_output_tab() {
+        let mut app = MainApp::with_profiles(
+            Profiles::default(),
+            None,
+            Vec::new(),
+            ConfigSaver::disabled(),
+        );
+        app.ansicht = Ansicht::SolverOutput;
+
+        app.handle_message(
+            Instant::now(),
+            SolverMessage::NewStepInfo(StepInfo {
+                step: 1,
+                ..Default::default()
+            }),
+        );
+        for (i, residual) in [1.0, 0.1, 0.01].into_iter().enumerate() {
+            app.handle_message(
+                Instant::now(),
+                SolverMessage::Residual(ResidualData {
+                    kind: ResidualKind::Force,
+                    step: 1,
+                    increment: 1,
+                    attempt: 1,
+                    total_iteration: i as u32 + 1,
+                    residual,
+                    correction: None,
+                }),
+            );
+        }
+
+        assert!(app.ansicht == Ansicht::SolverOutput);
+        assert_eq!(app.step_info.len(), 1);
+        assert_eq!(app.residual_data.len(), 3);
+    }
+
+    #[test]
+    fn records_iterations_of_converged_increments() {
+        let mut app = MainApp::with_profiles(
+            Profiles::default(),
+            None,
+            Vec::new(),
+            ConfigSaver::disabled(),
+        );
+        let info = |step, increment, attempt, iterations| StepInfo {
+            step,
+            increment,
+            attempt,
+            iterations,
+            ..Default::default()
+        };
+
+        app.handle_message(Instant::now(), SolverMessage::NewStepInfo(info(1, 0, 0, 0)));
+        for update in [
+            info(1, 1, 1, 3),
+            info(1, 2, 1, 9),
+            // Cut back: only the converged attempt counts.
+            info(1, 2, 2, 4),
+            info(1, 3, 1, 2),
+        ] {
+            app.handle_message(Instant::now(), SolverMessage::UpdateStepInfo(update));
+        }
+        app.handle_message(Instant::now(), SolverMessage::NewStepInfo(info(2, 0, 0, 0)));
+        app.handle_message(
+            Instant::now(),
+            SolverMessage::UpdateStepInfo(info(2, 1, 1, 5)),
+        );
+
+        assert_eq!(app.increment_iterations, [(1, 1, 3), (1, 2, 4), (1, 3, 2)]);
+    }
+
+    #[test]
+    fn formats_ages_in_their_largest_unit() {
+        assert_eq!(format_age(30), "<1m");
+        assert_eq!(format_age(5 * 60 + 59), "5m");
+        assert_eq!(format_age(3 * 3600), "3h");
+        assert_eq!(format_age(2 * 86400 + 3600), "2d");
+        assert_eq!(format_age(20 * 86400), "2w");
     }
 }
@@ -1,56 +1,649 @@
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::fs::{create_dir_all, File};
 use std::io::{Read, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
 use dirs::config_dir;
 
+/// Environment variable that overrides the directory the config is stored in.
+const CONFIG_DIR_ENV: &str = "CCX_RUNNER_CONFIG_DIR";
+
+/// Quiet period after the last save request before the config is written.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Maximum number of entries kept in `UserSetup::recent_projects`.
+const MAX_RECENT_PROJECTS: usize = 10;
+
+/// Name of the profile a flat (pre-profile) config is loaded into.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Layout version of `UserSetup` written by this build. Bump it when a field
+/// is renamed or changes meaning, and teach [`migrate`] the upgrade.
+pub const CONFIG_VERSION: u32 = 1;
+
+pub fn default_max_output_lines() -> usize {
+    50_000
+}
+
+pub fn default_divergence_threshold() -> u32 {
+    5
+}
+
+pub fn default_output_font_size() -> f32 {
+    12.0
+}
+
+pub fn default_input_flag() -> String {
+    "-i".to_string()
+}
+
+pub fn default_check_includes() -> bool {
+    true
+}
+
+pub fn default_silence_warning_secs() -> u64 {
+    30
+}
+
 pub fn default_num_cores() -> usize {
     std::thread::available_parallelism().map_or(1, |n| n.get())
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Where step and increment progress is read from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ParseSource {
+    #[default]
+    Stdout,
+    StaFile,
+}
+
+/// Color theme of the UI.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Theme {
+    /// Follow the operating system's dark/light setting.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// A named output filter that can be picked from the Solver Output tab.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedFilter {
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub regex: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UserSetup {
+    /// Layout version the setup was saved with; 0 for configs that predate it.
+    #[serde(default)]
+    pub version: u32,
     pub calculix_bin_path: PathBuf,
     pub project_dir_path: PathBuf,
     #[serde(default = "default_num_cores")]
     pub num_cores: usize,
+    /// Core counts of projects that don't use `num_cores`, keyed by project directory.
+    #[serde(default)]
+    pub project_cores: BTreeMap<PathBuf, usize>,
+    /// Free-form notes, keyed by project directory.
+    #[serde(default)]
+    pub project_notes: BTreeMap<PathBuf, String>,
+    /// Threads of the element loop (`OMP_NUM_THREADS`); `None` follows `num_cores`.
+    #[serde(default)]
+    pub omp_threads: Option<usize>,
+    /// Threads of the equation solver (`CCX_NPROC_EQUATION_SOLVER`); `None`
+    /// follows `num_cores`.
+    #[serde(default)]
+    pub solver_threads: Option<usize>,
+    /// Leave the thread-count variables ccx_runner was started with alone
+    /// instead of overriding them.
+    #[serde(default)]
+    pub inherit_thread_env: bool,
+    #[serde(default)]
+    pub last_inp_file: Option<PathBuf>,
+    #[serde(default)]
+    pub log_to_file: bool,
+    /// Additional environment variables, applied after the thread-count defaults.
+    #[serde(default)]
+    pub extra_env: Vec<(String, String)>,
+    /// Additional arguments passed to ccx after the job.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    #[serde(default)]
+    pub recent_projects: Vec<PathBuf>,
+    #[serde(default)]
+    pub parse_source: ParseSource,
+    /// Read the residuals from `<job>.cvg` instead of stdout.
+    #[serde(default)]
+    pub tail_cvg: bool,
+    #[serde(default = "default_max_output_lines")]
+    pub max_output_lines: usize,
+    #[serde(default)]
+    pub notify_on_finish: bool,
+    #[serde(default)]
+    pub kill_on_divergence: bool,
+    /// Number of consecutive residual increases that count as divergence.
+    #[serde(default = "default_divergence_threshold")]
+    pub divergence_threshold: u32,
+    #[serde(default)]
+    pub cgx_bin_path: Option<PathBuf>,
+    /// Inner window size at the last exit.
+    #[serde(default)]
+    pub window_size: Option<[f32; 2]>,
+    /// Outer window position at the last exit.
+    #[serde(default)]
+    pub window_pos: Option<[f32; 2]>,
+    /// Monospace font size of the solver output.
+    #[serde(default = "default_output_font_size")]
+    pub output_font_size: f32,
+    /// Wall-clock limit after which a run is killed, if any.
+    #[serde(default)]
+    pub max_runtime_secs: Option<u64>,
+    /// Also look for `.inp` files in subdirectories of the project.
+    #[serde(default)]
+    pub recursive_scan: bool,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Write `<job>.summary.json` after each run.
+    #[serde(default)]
+    pub write_summary: bool,
+    /// Flag that precedes the job name; empty passes the job positionally.
+    #[serde(default = "default_input_flag")]
+    pub input_flag: String,
+    /// Pass `<job>.inp` instead of `<job>`, for wrappers that expect the file name.
+    #[serde(default)]
+    pub job_with_extension: bool,
+    /// Convergence tolerance drawn as a reference line on the residual plot.
+    #[serde(default)]
+    pub residual_target: Option<f64>,
+    /// Look for missing `*INCLUDE` files before running.
+    #[serde(default = "default_check_includes")]
+    pub check_includes: bool,
+    /// Copy the input deck into the output before the solver's lines.
+    #[serde(default)]
+    pub echo_input: bool,
+    /// Inline `*INCLUDE`d files when echoing the input deck.
+    #[serde(default)]
+    pub echo_includes: bool,
+    /// Seconds without solver output after which a warning offers to stop the run.
+    #[serde(default = "default_silence_warning_secs")]
+    pub silence_warning_secs: u64,
+    /// Output filter of the last session.
+    #[serde(default)]
+    pub last_filter: String,
+    #[serde(default)]
+    pub saved_filters: Vec<SavedFilter>,
+    /// Command that ccx is run through, e.g. `wsl` or `mpirun -np 4`.
+    #[serde(default)]
+    pub launcher: Option<String>,
+    /// Directory ccx is run in and writes its results to instead of the input's
+    /// folder. The input deck is copied there before each run.
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
 }
 
 impl Default for UserSetup {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             calculix_bin_path: PathBuf::from(""),
             project_dir_path: PathBuf::from(""),
             num_cores: default_num_cores(),
+            project_cores: BTreeMap::new(),
+            project_notes: BTreeMap::new(),
+            omp_threads: None,
+            solver_threads: None,
+            inherit_thread_env: false,
+            last_inp_file: None,
+            log_to_file: false,
+            extra_env: Vec::new(),
+            extra_args: Vec::new(),
+            recent_projects: Vec::new(),
+            parse_source: ParseSource::default(),
+            tail_cvg: false,
+            max_output_lines: default_max_output_lines(),
+            notify_on_finish: false,
+            kill_on_divergence: false,
+            divergence_threshold: default_divergence_threshold(),
+            cgx_bin_path: None,
+            window_size: None,
+            window_pos: None,
+            output_font_size: default_output_font_size(),
+            max_runtime_secs: None,
+            recursive_scan: false,
+            theme: Theme::default(),
+            write_summary: false,
+            input_flag: default_input_flag(),
+            job_with_extension: false,
+            residual_target: None,
+            check_includes: default_check_includes(),
+            echo_input: false,
+            echo_includes: false,
+            silence_warning_secs: default_silence_warning_secs(),
+            last_filter: String::new(),
+            saved_filters: Vec::new(),
+            launcher: None,
+            output_dir: None,
+        }
+    }
+}
+
+impl UserSetup {
+    /// Cores used for the current project: its own count, if it has one, else `num_cores`.
+    pub fn cores(&self) -> usize {
+        self.project_cores
+            .get(&self.project_dir_path)
+            .copied()
+            .unwrap_or(self.num_cores)
+    }
+
+    /// The count [`cores`](Self::cores) reads, for editing.
+    pub fn cores_mut(&mut self) -> &mut usize {
+        match self.project_cores.get_mut(&self.project_dir_path) {
+            Some(cores) => cores,
+            None => &mut self.num_cores,
         }
     }
+
+    /// Moves the project directory `dir` to the front of the recent list.
+    pub fn remember_project(&mut self, dir: &Path) {
+        if dir.as_os_str().is_empty() {
+            return;
+        }
+        self.recent_projects.retain(|p| p != dir);
+        self.recent_projects.insert(0, dir.to_path_buf());
+        self.recent_projects.truncate(MAX_RECENT_PROJECTS);
+    }
+}
+
+/// Named `UserSetup`s, e.g. one per ccx build.
+#[derive(Serialize, Debug, Clone)]
+pub struct Profiles {
+    pub active: String,
+    pub profiles: BTreeMap<String, UserSetup>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), UserSetup::default())]),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Profiles {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Older configs store a single flat `UserSetup`; keep loading those.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Shape {
+            Profiles {
+                active: String,
+                profiles: BTreeMap<String, Value>,
+            },
+            Flat(Value),
+        }
+
+        Ok(match Shape::deserialize(deserializer)? {
+            Shape::Profiles { active, profiles } => Self {
+                active,
+                profiles: profiles
+                    .into_iter()
+                    .map(|(name, user_setup)| (name, migrate(user_setup)))
+                    .collect(),
+            },
+            Shape::Flat(user_setup) => Self {
+                active: DEFAULT_PROFILE.to_string(),
+                profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), migrate(user_setup))]),
+            },
+        })
+    }
+}
+
+/// Upgrades a saved `UserSetup` of any version to the current layout. Fields
+/// that are missing or no longer deserialize fall back to their defaults
+/// instead of discarding the whole setup.
+pub fn migrate(value: Value) -> UserSetup {
+    let Value::Object(fields) = value else {
+        return UserSetup::default();
+    };
+    // Renamed fields are remapped here, keyed on `fields["version"]`. Version 0
+    // (no `version` field) has the same layout as version 1.
+
+    let Ok(Value::Object(mut merged)) = serde_json::to_value(UserSetup::default()) else {
+        return UserSetup::default();
+    };
+    for (key, value) in fields {
+        let previous = merged.insert(key.clone(), value);
+        if serde_json::from_value::<UserSetup>(Value::Object(merged.clone())).is_err() {
+            eprintln!("Warning: ignoring invalid config value for '{}'", key);
+            match previous {
+                Some(previous) => merged.insert(key, previous),
+                None => merged.remove(&key),
+            };
+        }
+    }
+    let mut user_setup: UserSetup =
+        serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+    user_setup.version = CONFIG_VERSION;
+    user_setup
+}
+
+impl Profiles {
+    pub fn active_setup(&self) -> UserSetup {
+        self.profiles.get(&self.active).cloned().unwrap_or_default()
+    }
+}
+
+/// Resolves the config directory: `$CCX_RUNNER_CONFIG_DIR` if set, else the
+/// platform config dir, else the current working directory (e.g. in sandboxes
+/// without a home directory).
+fn config_dir_path() -> PathBuf {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    config_dir()
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("ccx_runner_rs")
+}
+
+/// The file the profiles are loaded from and saved to.
+pub fn config_path() -> PathBuf {
+    config_dir_path().join("config.json")
 }
 
-pub fn load() -> UserSetup {
-    let config_dir = config_dir().unwrap().join("ccx_runner_rs");
+pub fn load() -> Result<Profiles, std::io::Error> {
+    let config_dir = config_dir_path();
 
     if !config_dir.exists() {
-        create_dir_all(&config_dir).unwrap();
+        create_dir_all(&config_dir)?;
     };
 
-    let config_file = config_dir.join("config.json");
+    let config_file = config_path();
 
-    if config_file.exists() {
-        let mut file = File::open(config_file).unwrap();
+    if config_file.is_file() {
+        let mut file = File::open(&config_file)?;
         let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-        serde_json::from_str(&contents).unwrap_or_default()
+        file.read_to_string(&mut contents)?;
+        // The defaults are saved over the file later, so keep a copy for the user.
+        serde_json::from_str(&contents).map_err(|e| {
+            let backup = back_up(&config_file);
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "malformed config {} ({}): {}",
+                    config_file.display(),
+                    backup,
+                    e
+                ),
+            )
+        })
+    } else if config_file.exists() {
+        // E.g. a directory named `config.json`.
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{} is not a file", config_file.display()),
+        ))
     } else {
-        UserSetup::default()
+        Ok(Profiles::default())
     }
 }
 
-pub fn save(user_setup: &UserSetup) -> Result<(), std::io::Error> {
-    let config_dir = config_dir().unwrap().join("ccx_runner_rs");
-    let config_file = config_dir.join("config.json");
-    let json = serde_json::to_string_pretty(user_setup).unwrap();
+pub fn save(profiles: &Profiles) -> Result<(), std::io::Error> {
+    create_dir_all(config_dir_path())?;
+    let config_file = config_path();
+    let json = serde_json::to_string_pretty(profiles)?;
     let mut file = File::create(config_file)?;
     file.write_all(json.as_bytes())?;
 
     Ok(())
 }
+
+/// Copies an unreadable file to `<name>.bak` before it gets overwritten, and
+/// describes the outcome for a warning.
+fn back_up(path: &Path) -> String {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    match std::fs::copy(path, &backup) {
+        Ok(_) => format!("kept a copy as {}", backup.display()),
+        Err(e) => format!("couldn't copy it to {}: {}", backup.display(), e),
+    }
+}
+
+/// What [`ConfigSaver`] writes.
+enum SaveRequest {
+    Config(Profiles),
+    History(Vec<RunRecord>),
+}
+
+/// Saves the config and the run history on a background thread. Requests
+/// that arrive within [`SAVE_DEBOUNCE`] of each other are coalesced, and only
+/// the latest snapshot of each is written. Dropping the saver writes any
+/// pending snapshots and waits for them.
+pub struct ConfigSaver {
+    /// `None` for a saver that doesn't write.
+    sender: Option<Sender<SaveRequest>>,
+    results: Receiver<Result<(), std::io::Error>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ConfigSaver {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (result_sender, results) = mpsc::channel();
+        let thread = std::thread::spawn(move || {
+            while let Ok(first) = receiver.recv() {
+                let mut profiles = None;
+                let mut history = None;
+                let mut next = Some(first);
+                while let Some(request) = next {
+                    match request {
+                        SaveRequest::Config(newer) => profiles = Some(newer),
+                        SaveRequest::History(newer) => history = Some(newer),
+                    }
+                    next = receiver.recv_timeout(SAVE_DEBOUNCE).ok();
+                }
+                if let Some(profiles) = profiles {
+                    let _ = result_sender.send(save(&profiles));
+                }
+                if let Some(history) = history {
+                    let result = save_history(&history).map_err(|e| {
+                        std::io::Error::new(e.kind(), format!("run history: {}", e))
+                    });
+                    let _ = result_sender.send(result);
+                }
+            }
+        });
+        Self {
+            sender: Some(sender),
+            results,
+            thread: Some(thread),
+        }
+    }
+
+    /// A saver that never writes, for tests that must not touch the user's config.
+    #[cfg(test)]
+    pub fn disabled() -> Self {
+        let (_, results) = mpsc::channel();
+        Self {
+            sender: None,
+            results,
+            thread: None,
+        }
+    }
+
+    /// Queues `profiles` to be written once no newer request follows.
+    pub fn request(&self, profiles: Profiles) {
+        self.send(SaveRequest::Config(profiles));
+    }
+
+    /// Queues the run history to be written, like [`Self::request`].
+    pub fn request_history(&self, history: Vec<RunRecord>) {
+        self.send(SaveRequest::History(history));
+    }
+
+    fn send(&self, request: SaveRequest) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(request);
+        }
+    }
+
+    /// Outcome of the saves that finished since the last call, if any: the
+    /// first failure, or `Ok` if they all succeeded.
+    pub fn poll(&self) -> Option<Result<(), std::io::Error>> {
+        self.results
+            .try_iter()
+            .reduce(|outcome, next| outcome.and(next))
+    }
+}
+
+impl Drop for ConfigSaver {
+    fn drop(&mut self) {
+        // Disconnecting ends the debounce wait, so the pending save runs right away.
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                eprintln!("Config saver thread panicked");
+            }
+        }
+        for e in self.results.try_iter().filter_map(Result::err) {
+            eprintln!("Failed to save config on exit: {}", e);
+        }
+    }
+}
+
+/// Maximum number of runs kept in `runs.json`.
+const MAX_RUN_HISTORY: usize = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum RunOutcome {
+    Succeeded,
+    Failed,
+    /// Stopped by the user or one of the kill switches.
+    Stopped,
+}
+
+/// One entry of the run history.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunRecord {
+    pub job: String,
+    pub inp_file: PathBuf,
+    /// Start of the run in seconds since the Unix epoch.
+    pub started: u64,
+    pub duration_secs: f64,
+    pub outcome: RunOutcome,
+}
+
+/// Loads the run history, newest first. A missing or malformed file yields an
+/// empty history.
+pub fn load_history() -> Vec<RunRecord> {
+    let history_file = config_dir_path().join("runs.json");
+    let Ok(contents) = std::fs::read_to_string(&history_file) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: ignoring malformed run history {} ({}): {}",
+            history_file.display(),
+            back_up(&history_file),
+            e
+        );
+        Vec::new()
+    })
+}
+
+/// Adds `record` to the front of the history, dropping the oldest entries.
+pub fn record_run(history: &mut Vec<RunRecord>, record: RunRecord) {
+    history.insert(0, record);
+    history.truncate(MAX_RUN_HISTORY);
+}
+
+pub fn save_history(history: &[RunRecord]) -> Result<(), std::io::Error> {
+    let config_dir = config_dir_path();
+    create_dir_all(&config_dir)?;
+    let json = serde_json::to_string_pretty(history)?;
+    std::fs::write(config_dir.join("runs.json"), json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_num_cores, migrate, Profiles, CONFIG_VERSION, DEFAULT_PROFILE};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    /// A flat config as written before profiles and versions existed.
+    fn v0_config() -> serde_json::Value {
+        json!({
+            "calculix_bin_path": "/opt/ccx/ccx_2.21",
+            "project_dir_path": "/home/user/models",
+            "num_cores": 4,
+            "extra_args": ["-o", "exo"],
+            "theme": "Dark",
+        })
+    }
+
+    #[test]
+    fn migrates_a_v0_config() {
+        let user_setup = migrate(v0_config());
+        assert_eq!(user_setup.version, CONFIG_VERSION);
+        assert_eq!(
+            user_setup.calculix_bin_path,
+            PathBuf::from("/opt/ccx/ccx_2.21")
+        );
+        assert_eq!(
+            user_setup.project_dir_path,
+            PathBuf::from("/home/user/models")
+        );
+        assert_eq!(user_setup.num_cores, 4);
+        assert_eq!(user_setup.extra_args, ["-o", "exo"]);
+        // Fields the config predates get their defaults.
+        assert_eq!(user_setup.input_flag, "-i");
+        assert!(user_setup.check_includes);
+    }
+
+    #[test]
+    fn keeps_valid_fields_next_to_an_invalid_one() {
+        let mut config = v0_config();
+        config["num_cores"] = json!("four");
+        let user_setup = migrate(config);
+        assert_eq!(user_setup.num_cores, default_num_cores());
+        assert_eq!(
+            user_setup.project_dir_path,
+            PathBuf::from("/home/user/models")
+        );
+    }
+
+    #[test]
+    fn loads_flat_v0_configs_into_the_default_profile() {
+        let profiles: Profiles = serde_json::from_value(v0_config()).unwrap();
+        assert_eq!(profiles.active, DEFAULT_PROFILE);
+        let user_setup = profiles.active_setup();
+        assert_eq!(user_setup.version, CONFIG_VERSION);
+        assert_eq!(user_setup.num_cores, 4);
+    }
+
+    #[test]
+    fn migrates_every_profile() {
+        let config = json!({
+            "active": "cluster",
+            "profiles": { "cluster": v0_config(), "laptop": { "num_cores": "many" } },
+        });
+        let profiles: Profiles = serde_json::from_value(config).unwrap();
+        assert_eq!(profiles.active_setup().num_cores, 4);
+        assert_eq!(profiles.profiles["laptop"].num_cores, default_num_cores());
+        assert_eq!(profiles.profiles["laptop"].version, CONFIG_VERSION);
+    }
+}
@@ -0,0 +1,172 @@
+/// A single AND term of a filter clause.
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    text: String,
+    negated: bool,
+}
+
+/// A compiled DNF filter query: OR clauses separated by `|`, each made of AND
+/// terms separated by `&`. A term prefixed with `!` or `-` must NOT be
+/// contained in the line. Matching is case-insensitive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    or_clauses: Vec<Vec<Term>>,
+}
+
+pub fn compile(query: &str) -> Filter {
+    // "a & !b | c" -> OR clauses: [[a, !b], [c]]
+    let or_clauses = query
+        .split('|')
+        .map(|or_part| {
+            or_part
+                .split('&')
+                .map(str::trim)
+                .map(|term| match term.strip_prefix(['!', '-']) {
+                    Some(negated) => Term {
+                        text: negated.trim().to_lowercase(),
+                        negated: true,
+                    },
+                    None => Term {
+                        text: term.to_lowercase(),
+                        negated: false,
+                    },
+                })
+                .filter(|term| !term.text.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|and_terms| !and_terms.is_empty())
+        .collect();
+
+    Filter { or_clauses }
+}
+
+impl Filter {
+    /// An empty filter matches every line.
+    pub fn is_empty(&self) -> bool {
+        self.or_clauses.is_empty()
+    }
+
+    pub fn matches(&self, line: &str) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let lower_line = line.to_lowercase();
+        // A line matches if it matches ANY of the OR clauses
+        self.or_clauses.iter().any(|and_terms| {
+            // An OR clause matches if ALL of its AND terms hold
+            and_terms
+                .iter()
+                .all(|term| lower_line.contains(term.text.as_str()) != term.negated)
+        })
+    }
+}
+
+/// The non-empty OR clauses of a query, trimmed.
+fn clauses(query: &str) -> impl Iterator<Item = &str> {
+    query.split('|').map(str::trim).filter(|c| !c.is_empty())
+}
+
+/// Whether every OR clause of `preset` is one of the OR clauses of `query`.
+pub fn contains_clauses(query: &str, preset: &str) -> bool {
+    clauses(preset).all(|clause| clauses(query).any(|c| c == clause))
+}
+
+/// Removes the OR clauses of `preset` from `query` if they are all in it,
+/// else adds the missing ones.
+pub fn toggle_clauses(query: &str, preset: &str) -> String {
+    let mut result: Vec<&str> = clauses(query).collect();
+    if contains_clauses(query, preset) {
+        result.retain(|c| !clauses(preset).any(|clause| clause == *c));
+    } else {
+        for clause in clauses(preset) {
+            if !result.contains(&clause) {
+                result.push(clause);
+            }
+        }
+    }
+    result.join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile, contains_clauses, toggle_clauses};
+
+    #[test]
+    fn empty_query_matches_all() {
+        assert!(compile("").matches("anything"));
+        assert!(compile("   ").matches(""));
+        assert!(compile(" | & ").matches("anything"));
+    }
+
+    #[test]
+    fn single_term() {
+        let filter = compile("force");
+        assert!(filter.matches("largest residual force= 1.0"));
+        assert!(!filter.matches("iteration 2"));
+    }
+
+    #[test]
+    fn and_requires_all_terms() {
+        let filter = compile("force & iteration");
+        assert!(filter.matches("force at iteration 2"));
+        assert!(!filter.matches("force only"));
+    }
+
+    #[test]
+    fn or_requires_any_clause() {
+        let filter = compile("force | convergence");
+        assert!(filter.matches("force"));
+        assert!(filter.matches("convergence"));
+        assert!(!filter.matches("iteration"));
+    }
+
+    #[test]
+    fn leading_and_trailing_separators_are_ignored() {
+        assert_eq!(compile("| force & |"), compile("force"));
+        assert_eq!(compile("& force &"), compile("force"));
+    }
+
+    #[test]
+    fn matching_is_case_insensitive() {
+        assert!(compile("CONVERGENCE").matches("convergence"));
+        assert!(compile("convergence").matches("CONVERGENCE"));
+    }
+
+    #[test]
+    fn negated_terms_exclude_lines() {
+        assert!(compile("iteration & !convergence").matches("iteration 3"));
+        assert!(!compile("iteration & !convergence").matches("iteration 3 convergence"));
+        assert!(!compile("iteration & -convergence").matches("iteration 3 convergence"));
+        assert!(compile("!iteration").matches("largest residual force"));
+        assert!(compile("force & !").matches("force"));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let filter = compile("force & iteration | CONVERGENCE & !step");
+        assert!(filter.matches("force at iteration 2"));
+        assert!(!filter.matches("force only"));
+        assert!(filter.matches("Convergence reached"));
+        assert!(!filter.matches("convergence in step 1"));
+    }
+
+    #[test]
+    fn presets_toggle_as_or_clauses() {
+        let query = toggle_clauses("", "*error");
+        assert_eq!(query, "*error");
+        let query = toggle_clauses(&query, "contact | gap");
+        assert_eq!(query, "*error | contact | gap");
+        assert!(contains_clauses(&query, "gap|contact"));
+        assert!(!contains_clauses(&query, "contact | *warning"));
+        assert_eq!(toggle_clauses(&query, "*error"), "contact | gap");
+    }
+
+    #[test]
+    fn toggling_keeps_hand_written_clauses() {
+        let query = toggle_clauses("iteration & !force", "*warning");
+        assert_eq!(query, "iteration & !force | *warning");
+        assert_eq!(toggle_clauses(&query, "*warning"), "iteration & !force");
+        // Partially present presets are completed rather than removed.
+        assert_eq!(toggle_clauses("contact", "contact | gap"), "contact | gap");
+    }
+}
@@ -0,0 +1,358 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Keywords that start a time-stepping procedure inside a `*STEP`.
+const TIME_PROCEDURES: [&str; 7] = [
+    "*STATIC",
+    "*DYNAMIC",
+    "*HEAT TRANSFER",
+    "*COUPLED TEMPERATURE-DISPLACEMENT",
+    "*UNCOUPLED TEMPERATURE-DISPLACEMENT",
+    "*VISCO",
+    "*MODAL DYNAMIC",
+];
+
+/// Default step time period CalculiX assumes when the data line omits it.
+const DEFAULT_STEP_PERIOD: f64 = 1.0;
+
+/// Whether `path` has an `.inp` extension, in any case (e.g. `MODEL.INP`).
+pub fn is_inp_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("inp"))
+}
+
+/// Collects the `.inp` files in `dir`, descending at most `max_depth` levels
+/// into subdirectories. Unreadable directories are skipped.
+pub fn find_inp_files(dir: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            if max_depth > 0 {
+                files.extend(find_inp_files(&path, max_depth - 1));
+            }
+        } else if is_inp_file(&path) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Returns the files referenced by `*INCLUDE, INPUT=...` that don't exist,
+/// following includes of includes. ccx resolves relative paths against its
+/// working directory `base`, at every level of nesting.
+pub fn check_includes(inp: &Path, base: &Path) -> Vec<PathBuf> {
+    let mut missing = Vec::new();
+    let mut visited = vec![inp.to_path_buf()];
+    let mut pending = vec![inp.to_path_buf()];
+
+    while let Some(file) = pending.pop() {
+        let Ok(deck) = fs::read_to_string(&file) else {
+            continue;
+        };
+        for include in parse_includes(&deck) {
+            let path = base.join(include);
+            if visited.contains(&path) {
+                continue;
+            }
+            visited.push(path.clone());
+            if path.is_file() {
+                pending.push(path);
+            } else {
+                missing.push(path);
+            }
+        }
+    }
+
+    missing
+}
+
+/// Reads the lines of an input deck. With `follow_includes`, the contents of
+/// each readable `*INCLUDE` are inserted after its keyword line, framed by
+/// `**` comments naming the file. Like in [`check_includes`], relative paths
+/// are resolved against the working directory `base`. Includes that are
+/// already being expanded are not followed again.
+pub fn read_deck_lines(
+    inp: &Path,
+    base: &Path,
+    follow_includes: bool,
+) -> std::io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut stack = vec![inp.to_path_buf()];
+    expand_deck(inp, base, follow_includes, &mut stack, &mut lines)?;
+    Ok(lines)
+}
+
+fn expand_deck(
+    file: &Path,
+    base: &Path,
+    follow_includes: bool,
+    stack: &mut Vec<PathBuf>,
+    lines: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let deck = fs::read_to_string(file)?;
+    for line in deck.lines() {
+        lines.push(line.to_string());
+        if !follow_includes {
+            continue;
+        }
+        let Some(include) = include_input(line) else {
+            continue;
+        };
+        let path = base.join(include);
+        if stack.contains(&path) || !path.is_file() {
+            continue;
+        }
+        lines.push(format!("** >>> {}", path.display()));
+        stack.push(path.clone());
+        let expanded = expand_deck(&path, base, follow_includes, stack, lines);
+        stack.pop();
+        if let Err(e) = expanded {
+            lines.push(format!("** could not read {}: {}", path.display(), e));
+        }
+        lines.push(format!("** <<< {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Reads the deck with its relative `*INCLUDE` paths made absolute, resolved
+/// against the working directory `base`, so it can be run from another
+/// directory. Fails if an included file includes others by a relative path,
+/// since ccx would resolve those against the new directory.
+pub fn rebase_includes(inp: &Path, base: &Path) -> std::io::Result<String> {
+    let deck = fs::read_to_string(inp)?;
+    let mut rebased = String::new();
+    let mut visited = vec![inp.to_path_buf()];
+    let mut pending = Vec::new();
+    for line in deck.lines() {
+        match include_input(line) {
+            Some(include) => {
+                let path = std::path::absolute(base.join(include))?;
+                rebased.push_str(&format!("*INCLUDE, INPUT={}", path.display()));
+                if !visited.contains(&path) {
+                    visited.push(path.clone());
+                    pending.push(path);
+                }
+            }
+            None => rebased.push_str(line),
+        }
+        rebased.push('\n');
+    }
+
+    while let Some(file) = pending.pop() {
+        // Missing includes are reported by `check_includes`.
+        let Ok(nested) = fs::read_to_string(&file) else {
+            continue;
+        };
+        for include in parse_includes(&nested) {
+            let path = PathBuf::from(&include);
+            if path.is_relative() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} includes {} by a relative path, which can't be resolved from another working directory",
+                        file.display(),
+                        include
+                    ),
+                ));
+            }
+            if !visited.contains(&path) {
+                visited.push(path.clone());
+                pending.push(path);
+            }
+        }
+    }
+
+    Ok(rebased)
+}
+
+/// Reads the `INPUT=` parameter of every `*INCLUDE` in an input deck.
+fn parse_includes(deck: &str) -> Vec<String> {
+    deck.lines().filter_map(include_input).collect()
+}
+
+/// The `INPUT=` parameter of a line, if it is an `*INCLUDE`.
+fn include_input(line: &str) -> Option<String> {
+    let line = line.trim();
+    if !line.to_uppercase().starts_with("*INCLUDE") {
+        return None;
+    }
+    line.split(',').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("INPUT")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Reads the time period of every `*STEP` in an input deck, in order.
+/// The period is the second value on the data line following the procedure
+/// keyword (e.g. `*STATIC`).
+pub fn parse_step_periods(deck: &str) -> Vec<f64> {
+    let mut periods = Vec::new();
+    let mut awaiting_data_line = false;
+
+    for line in deck.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("**") {
+            continue;
+        }
+        let upper = line.to_uppercase();
+
+        if awaiting_data_line {
+            awaiting_data_line = false;
+            if !upper.starts_with('*') {
+                let period = line
+                    .split(',')
+                    .nth(1)
+                    .and_then(|s| s.trim().parse::<f64>().ok());
+                if let (Some(period), Some(last)) = (period, periods.last_mut()) {
+                    *last = period;
+                }
+                continue;
+            }
+        }
+
+        if upper.starts_with("*STEP") {
+            periods.push(DEFAULT_STEP_PERIOD);
+        } else if TIME_PROCEDURES.iter().any(|kw| upper.starts_with(kw)) {
+            awaiting_data_line = !periods.is_empty();
+        }
+    }
+
+    periods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_includes, find_inp_files, is_inp_file, read_deck_lines, rebase_includes};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fresh directory under the system temp dir, removed again on drop,
+    /// also when the test panics.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "ccx_runner_test_{}_{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl std::ops::Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn inp_extension_ignores_case() {
+        assert!(is_inp_file(Path::new("job.inp")));
+        assert!(is_inp_file(Path::new("MODEL.INP")));
+        assert!(is_inp_file(Path::new("Mixed.Inp")));
+        assert!(!is_inp_file(Path::new("job.frd")));
+        assert!(!is_inp_file(Path::new("inp")));
+    }
+
+    #[test]
+    fn finds_mixed_case_inp_files() {
+        let dir = TempDir::new();
+        for name in ["lower.inp", "UPPER.INP", "results.frd"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        let mut found: Vec<String> = find_inp_files(&dir, 0)
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+            .collect();
+        found.sort();
+
+        assert_eq!(found, ["UPPER.INP", "lower.inp"]);
+    }
+
+    #[test]
+    fn resolves_nested_includes_from_the_working_directory() {
+        let dir = TempDir::new();
+        fs::create_dir(dir.join("mesh")).unwrap();
+        let main = dir.join("main.inp");
+        fs::write(&main, "*INCLUDE, INPUT=mesh/part.inp").unwrap();
+        // Relative to the working directory, not to `mesh/`.
+        fs::write(dir.join("mesh/part.inp"), "*INCLUDE, INPUT=mesh/nodes.inp").unwrap();
+        fs::write(dir.join("mesh/nodes.inp"), "*NODE").unwrap();
+
+        let found = check_includes(&main, &dir);
+        let missing = check_includes(&main, &dir.join("mesh"));
+
+        assert!(found.is_empty());
+        assert_eq!(missing, [dir.join("mesh/mesh/part.inp")]);
+    }
+
+    #[test]
+    fn inlines_includes_once() {
+        let dir = TempDir::new();
+        let main = dir.join("main.inp");
+        let mesh = dir.join("mesh.inp");
+        fs::write(&main, "*INCLUDE, INPUT=mesh.inp\n*STEP").unwrap();
+        fs::write(&mesh, "*NODE\n*INCLUDE, INPUT=main.inp").unwrap();
+
+        let plain = read_deck_lines(&main, &dir, false).unwrap();
+        let expanded = read_deck_lines(&main, &dir, true).unwrap();
+
+        assert_eq!(plain, ["*INCLUDE, INPUT=mesh.inp", "*STEP"]);
+        assert_eq!(
+            expanded,
+            [
+                "*INCLUDE, INPUT=mesh.inp".to_string(),
+                format!("** >>> {}", mesh.display()),
+                "*NODE".to_string(),
+                "*INCLUDE, INPUT=main.inp".to_string(),
+                format!("** <<< {}", mesh.display()),
+                "*STEP".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn rebases_relative_includes() {
+        let dir = TempDir::new();
+        fs::create_dir(dir.join("mesh")).unwrap();
+        let main = dir.join("main.inp");
+        let nested = dir.join("nested.inp");
+        fs::write(&main, "*INCLUDE, INPUT=mesh/part.inp\n*STEP").unwrap();
+        fs::write(dir.join("mesh/part.inp"), "*NODE").unwrap();
+        fs::write(&nested, "*INCLUDE, INPUT=main.inp").unwrap();
+
+        let rebased = rebase_includes(&main, &dir);
+        let rejected = rebase_includes(&nested, &dir);
+
+        assert_eq!(
+            rebased.unwrap(),
+            format!(
+                "*INCLUDE, INPUT={}\n*STEP\n",
+                dir.join("mesh/part.inp").display()
+            )
+        );
+        // `main.inp` itself includes `mesh/part.inp` relatively.
+        assert!(rejected.is_err());
+    }
+}
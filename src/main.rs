@@ -2,18 +2,61 @@
 
 mod app;
 mod config;
+mod filter;
+mod inp;
 mod solver;
 
-use app::MainApp;
+use app::{LaunchOptions, MainApp};
+use std::path::PathBuf;
+
+/// Reads `[--run] [<file>.inp]`, e.g. when the app is associated with `.inp` files.
+fn parse_args() -> LaunchOptions {
+    let mut launch = LaunchOptions::default();
+    for arg in std::env::args_os().skip(1) {
+        if arg == "--run" {
+            launch.run = true;
+            continue;
+        }
+        let path = PathBuf::from(&arg);
+        if !inp::is_inp_file(&path) {
+            eprintln!("Warning: ignoring argument {}", path.display());
+            continue;
+        }
+        // The project directory is derived from the file, so it must be absolute.
+        launch.inp_file = Some(match std::env::current_dir() {
+            Ok(cwd) if path.is_relative() => cwd.join(path),
+            _ => path,
+        });
+    }
+    launch
+}
 
 fn main() -> eframe::Result<()> {
+    let launch = parse_args();
+    let (profiles, config_warning) = match config::load() {
+        Ok(profiles) => (profiles, None),
+        Err(e) => {
+            let warning = format!("Couldn't load config, using defaults: {}", e);
+            eprintln!("Warning: {}", warning);
+            (config::Profiles::default(), Some(warning))
+        }
+    };
+    let user_setup = profiles.active_setup();
+
+    let mut viewport = eframe::egui::ViewportBuilder::default()
+        .with_inner_size(user_setup.window_size.unwrap_or([800.0, 600.0]));
+    if let Some(pos) = user_setup.window_pos {
+        viewport = viewport.with_position(pos);
+    }
     let options = eframe::NativeOptions {
-        viewport: eframe::egui::ViewportBuilder::default().with_inner_size([800.0, 600.0]),
+        viewport,
+        // `MainApp` applies the configured theme, including following the system.
+        follow_system_theme: false,
         ..Default::default()
     };
     eframe::run_native(
         "CalculiX Solution Monitor",
         options,
-        Box::new(|cc| Ok(Box::new(MainApp::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(MainApp::new(cc, profiles, config_warning, launch)))),
     )
 }
\ No newline at end of file
@@ -1,7 +1,13 @@
-use std::io::{BufRead, BufReader};
-use std::process::{Child, Command, Stdio};
-use std::sync::mpsc::Sender;
+use crate::config::UserSetup;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{SendError, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Default)]
 pub struct StepInfo {
@@ -10,89 +16,864 @@ pub struct StepInfo {
     pub attempt: u32,
     pub iterations: u32,
     pub total_time: f64,
+    pub step_time: f64,
+    /// Time period of the step as defined in the input deck, if known.
+    pub step_period: Option<f64>,
+    /// Number of times an increment of this step was cut back.
+    pub cutbacks: u32,
+    /// Time size of the current increment.
+    pub dtime: f64,
+    /// Solution method announced for the step, e.g. `Newton-Raphson + Line search`.
+    pub method: Option<String>,
+}
+
+/// Quantity a residual belongs to; coupled analyses print one of each per iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResidualKind {
+    Force,
+    /// Heat flux of thermal and thermo-mechanical steps.
+    Flux,
+}
+
+impl ResidualKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ResidualKind::Force => "force",
+            ResidualKind::Flux => "flux",
+        }
+    }
+}
+
+/// Recognizes `largest residual force=` and `largest residual flux=` lines.
+fn residual_kind(line: &str) -> Option<ResidualKind> {
+    let line = line.trim();
+    if line.starts_with("largest residual force=") {
+        Some(ResidualKind::Force)
+    } else if line.starts_with("largest residual flux=") {
+        Some(ResidualKind::Flux)
+    } else {
+        None
+    }
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct ResidualData {
+    pub kind: ResidualKind,
     pub step: u32,
+    pub increment: u32,
+    /// Attempt of the increment, so a cut-back retry gets its own series.
+    pub attempt: u32,
+    /// Running iteration count over the whole run, used as the plot's x axis.
     pub total_iteration: u32,
     pub residual: f64,
+    /// Size of the solution correction, in percent; only the `.cvg` file reports it.
+    pub correction: Option<f64>,
+}
+
+/// An `*ERROR` or `*WARNING` with the lines ccx printed right after it,
+/// which usually name the offending element or node.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub detail: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Whole numbers in the message and detail, e.g. element or node ids.
+    pub fn numbers(&self) -> Vec<&str> {
+        std::iter::once(&self.message)
+            .chain(&self.detail)
+            .flat_map(|line| line.split(|c: char| !c.is_ascii_alphanumeric()))
+            .filter(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()))
+            .collect()
+    }
+}
+
+/// Most lines after an `*ERROR` / `*WARNING` that are kept as its detail.
+const MAX_DIAGNOSTIC_DETAIL_LINES: usize = 5;
+
+/// Whether `line` is regular ccx progress output, which ends the detail of
+/// a preceding `*ERROR` / `*WARNING` even without a blank line in between.
+fn is_progress_line(line: &str) -> bool {
+    parse_step_header(line).is_some()
+        || parse_increment(line).is_some()
+        || line.trim().starts_with("iteration ")
+        || residual_kind(line).is_some()
+        || parse_memory_line(line).is_some()
+        || parse_timing_line(line).is_some()
+}
+
+/// Size of the system of equations, as printed while ccx sets up the matrix.
+#[derive(Debug, Clone, Default)]
+pub struct ModelInfo {
+    pub num_equations: Option<u64>,
+    pub num_nonzeros: Option<u64>,
+    /// Peak memory ccx reported as needed, in MB.
+    pub memory_needed_mb: Option<f64>,
+    /// Peak memory ccx reported as used, in MB.
+    pub memory_used_mb: Option<f64>,
+}
+
+/// Counts that ccx prints on the line after their label.
+enum ModelCount {
+    Equations,
+    Nonzeros,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MemoryReport {
+    Needed,
+    Used,
+}
+
+/// Reads memory reports such as `using up to 1234 MB of memory`, in MB.
+fn parse_memory_line(line: &str) -> Option<(MemoryReport, f64)> {
+    let lower = line.to_lowercase();
+    if !lower.contains("memory") {
+        return None;
+    }
+    let words: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || c == ':' || c == '=')
+        .filter(|word| !word.is_empty())
+        .collect();
+    let megabytes = words.windows(2).find_map(|pair| {
+        let value = pair[0].parse::<f64>().ok()?;
+        match pair[1] {
+            "kb" => Some(value / 1024.0),
+            "mb" => Some(value),
+            "gb" => Some(value * 1024.0),
+            _ => None,
+        }
+    })?;
+    let report = if lower.contains("need") || lower.contains("requir") {
+        MemoryReport::Needed
+    } else {
+        MemoryReport::Used
+    };
+    Some((report, megabytes))
+}
+
+/// Reads the timings ccx prints at the end of a run, such as
+/// `Total CalculiX Time: 12.3` or `Time for equation solving: 4.5`.
+fn parse_timing_line(line: &str) -> Option<(String, f64)> {
+    let (label, value) = line.split_once(':').or_else(|| line.split_once('='))?;
+    let label = label.trim();
+    let lower = label.to_lowercase();
+    let is_timing = lower.contains("cpu time")
+        || lower.contains("calculix time")
+        || lower.starts_with("time for")
+        || lower.starts_with("time needed");
+    if !is_timing {
+        return None;
+    }
+    let secs = value.split_whitespace().next()?.parse().ok()?;
+    Some((label.to_string(), secs))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Detects the message CalculiX prints when it cuts back the increment size.
+pub fn is_cutback_line(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    lower.contains("increment size is decreased") || lower.contains("will be divided by")
+}
+
+/// Recognizes the header ccx prints when a step starts and returns the step
+/// number. Accepts padded (`STEP            1`), letter-spaced (`S T E P 1`)
+/// and decorated (`--- STEP 1 ---`) variants.
+pub fn parse_step_header(line: &str) -> Option<u32> {
+    let line = line.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+    let digits_start = line.find(|c: char| c.is_ascii_digit())?;
+    let (label, number) = line.split_at(digits_start);
+    let label: String = label.chars().filter(|c| !c.is_whitespace()).collect();
+    if label != "STEP" {
+        return None;
+    }
+    number.trim_end().parse().ok()
+}
+
+/// Recognizes the line ccx prints when an increment attempt starts and returns
+/// `(increment, attempt)`. Tolerates padding, punctuation such as
+/// `increment 2, attempt 1` and a missing attempt, which counts as the first.
+pub fn parse_increment(line: &str) -> Option<(u32, u32)> {
+    let mut words = line
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty());
+    if !words.next()?.eq_ignore_ascii_case("increment") {
+        return None;
+    }
+    let increment = words.next()?.parse().ok()?;
+    let attempt = match words.next() {
+        None => 1,
+        Some(word) if word.eq_ignore_ascii_case("attempt") => words.next()?.parse().ok()?,
+        Some(_) => return None,
+    };
+    Some((increment, attempt))
+}
+
+/// Recognizes the banners ccx prints for the solution method of a step, e.g.
+/// `Newton-Raphson iterative procedure is active.`
+fn solution_method(line: &str) -> Option<&'static str> {
+    let lower = line.to_lowercase();
+    if lower.contains("quasinewton") || lower.contains("quasi-newton") {
+        Some("Quasi-Newton")
+    } else if lower.contains("line search") {
+        Some("Line search")
+    } else if lower.contains("newton-raphson") {
+        Some("Newton-Raphson")
+    } else {
+        None
+    }
+}
+
+/// Classifies CalculiX's inline `*ERROR` / `*WARNING` markers.
+pub fn diagnostic_severity(line: &str) -> Option<Severity> {
+    if line.contains("*ERROR") {
+        Some(Severity::Error)
+    } else if line.contains("*WARNING") {
+        Some(Severity::Warning)
+    } else {
+        None
+    }
 }
 
 pub enum SolverMessage {
     Line(String),
+    StderrLine(String),
+    Diagnostic(Diagnostic),
     NewStepInfo(StepInfo),
     UpdateStepInfo(StepInfo),
     Residual(ResidualData),
     ResetResiduals,
+    ModelInfo(ModelInfo),
+    /// Wall-clock time an increment took, sent when the next one starts.
+    IncrementTiming {
+        increment: u32,
+        secs: f64,
+    },
+    /// A timing ccx reported, e.g. the total CPU time at the end of the run.
+    Timing {
+        phase: String,
+        secs: f64,
+    },
+    /// An input check reached the first step or an error and killed the solver.
+    InputChecked {
+        reached_step: bool,
+    },
+    /// The solver process has exited.
+    Finished(ExitStatus),
+}
+
+/// A [`SolverMessage`] stamped with the moment its reader thread produced it,
+/// so timings don't depend on how many messages pile up between frames.
+pub struct TimedMessage {
+    pub at: Instant,
+    pub message: SolverMessage,
+}
+
+/// Sends [`SolverMessage`]s as [`TimedMessage`]s stamped with the current time.
+#[derive(Clone)]
+pub struct MessageSender(Sender<TimedMessage>);
+
+impl MessageSender {
+    pub fn new(sender: Sender<TimedMessage>) -> Self {
+        Self(sender)
+    }
+
+    pub fn send(&self, message: SolverMessage) -> Result<(), SendError<TimedMessage>> {
+        self.0.send(TimedMessage {
+            at: Instant::now(),
+            message,
+        })
+    }
+}
+
+/// Checks that the CalculiX binary exists and is executable, so a bad path is
+/// reported before anything gets spawned.
+pub fn validate_ccx_binary(ccx_path: &Path) -> Result<(), String> {
+    if ccx_path.as_os_str().is_empty() {
+        return Err("No CalculiX binary configured.".to_string());
+    }
+    let resolved = resolve_binary(ccx_path)
+        .ok_or_else(|| format!("'{}' does not exist.", ccx_path.display()))?;
+    if !resolved.is_file() {
+        return Err(format!("'{}' is not a file.", resolved.display()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&resolved)
+            .map_err(|e| e.to_string())?
+            .permissions()
+            .mode();
+        if mode & 0o111 == 0 {
+            return Err(format!("'{}' is not executable.", resolved.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Validates what actually gets spawned. With a launcher that is the launcher
+/// program, since the ccx path is then resolved by it (e.g. inside WSL).
+pub fn validate_ccx_setup(setup: &UserSetup) -> Result<(), String> {
+    let launcher = launcher_args(setup);
+    let Some(program) = launcher.first() else {
+        return validate_ccx_binary(&setup.calculix_bin_path);
+    };
+    if setup.calculix_bin_path.as_os_str().is_empty() {
+        return Err("No CalculiX binary configured.".to_string());
+    }
+    validate_ccx_binary(Path::new(program)).map_err(|e| format!("Launcher: {}", e))
+}
+
+fn launcher_args(setup: &UserSetup) -> Vec<String> {
+    setup
+        .launcher
+        .as_deref()
+        .map(split_args)
+        .unwrap_or_default()
+}
+
+/// Starts a ccx invocation, through the configured launcher if there is one.
+fn ccx_command(setup: &UserSetup) -> Command {
+    let mut launcher = launcher_args(setup).into_iter();
+    match launcher.next() {
+        Some(program) => {
+            let mut command = Command::new(program);
+            command.args(launcher).arg(&setup.calculix_bin_path);
+            command
+        }
+        None => Command::new(&setup.calculix_bin_path),
+    }
+}
+
+fn resolve_binary(ccx_path: &Path) -> Option<PathBuf> {
+    if ccx_path.exists() {
+        return Some(ccx_path.to_path_buf());
+    }
+    // Bare program names are looked up in PATH, just like `Command::new` does.
+    if ccx_path.components().count() == 1 {
+        let paths = std::env::var_os("PATH")?;
+        return std::env::split_paths(&paths)
+            .map(|dir| dir.join(ccx_path))
+            .find(|candidate| candidate.is_file());
+    }
+    None
+}
+
+/// Splits a command line on whitespace, keeping single- or double-quoted
+/// sections together as one argument.
+pub fn split_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+fn quote_arg(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(char::is_whitespace) {
+        format!("\"{}\"", arg)
+    } else {
+        arg.to_string()
+    }
 }
 
-pub fn spawn_process(
-    ccx_path: &std::path::Path,
-    project_dir: &std::path::Path,
-    job_name: &str,
-    num_cores: usize,
-) -> Result<Child, std::io::Error> {
-    let num_cores = num_cores.to_string();
-    Command::new(ccx_path)
-        .arg("-i")
-        .arg(job_name)
-        .env("OMP_NUM_THREADS", &num_cores)
-        .env("CCX_NPROC", &num_cores)
-        .current_dir(project_dir)
+/// Inverse of [`split_args`], quoting arguments that contain whitespace.
+pub fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| quote_arg(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The job name ccx expects for an input deck, i.e. its file name without `.inp`.
+pub fn job_name(inp_path: &Path) -> &str {
+    inp_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+}
+
+/// The folder of the input deck, or the project directory for a bare file name.
+pub fn job_dir<'a>(setup: &'a UserSetup, inp_path: &'a Path) -> &'a Path {
+    inp_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(&setup.project_dir_path)
+}
+
+/// Whether `<job>.frd` was written after the input deck last changed, so that
+/// running the job again would only overwrite current results.
+pub fn results_up_to_date(setup: &UserSetup, inp_path: &Path) -> bool {
+    let frd_path = results_dir(setup, inp_path).join(format!("{}.frd", job_name(inp_path)));
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(inp_path), modified(&frd_path)) {
+        (Some(inp), Some(frd)) => frd > inp,
+        _ => false,
+    }
+}
+
+/// The directory ccx runs in and writes its results to: the configured output
+/// directory, if any, else [`job_dir`]. Everything that reads a job's files
+/// looks for them here.
+pub fn results_dir<'a>(setup: &'a UserSetup, inp_path: &'a Path) -> &'a Path {
+    setup
+        .output_dir
+        .as_deref()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| job_dir(setup, inp_path))
+}
+
+/// Variables that set ccx's thread counts.
+pub const THREAD_ENV_VARS: [&str; 3] =
+    ["OMP_NUM_THREADS", "CCX_NPROC", "CCX_NPROC_EQUATION_SOLVER"];
+
+/// The [`THREAD_ENV_VARS`] set in ccx_runner's own environment, with their values.
+pub fn inherited_thread_env() -> Vec<(&'static str, String)> {
+    THREAD_ENV_VARS
+        .into_iter()
+        .filter_map(|key| Some((key, std::env::var(key).ok()?)))
+        .collect()
+}
+
+/// Assembles the ccx invocation for `inp_path`, run from [`results_dir`]. Shared
+/// by [`spawn_process`] and [`build_command_preview`] so the preview always
+/// matches what runs.
+fn build_command(setup: &UserSetup, inp_path: &Path) -> Command {
+    let cores = setup.cores();
+    let thread_counts = [
+        setup.omp_threads.unwrap_or(cores),
+        cores,
+        setup.solver_threads.unwrap_or(cores),
+    ];
+    let mut command = ccx_command(setup);
+    let input_flag = setup.input_flag.trim();
+    if !input_flag.is_empty() {
+        command.arg(input_flag);
+    }
+    // ccx names its results after the job, so it is always passed by name; see
+    // `copy_deck_to_results_dir` for runs outside the input's folder.
+    if setup.job_with_extension {
+        command.arg(inp_path.file_name().unwrap_or_default());
+    } else {
+        command.arg(job_name(inp_path));
+    }
+    command
+        .args(&setup.extra_args)
+        .current_dir(results_dir(setup, inp_path));
+    for (key, threads) in THREAD_ENV_VARS.into_iter().zip(thread_counts) {
+        // Inherited values win over the counts above if the user asked for that.
+        if !(setup.inherit_thread_env && std::env::var_os(key).is_some()) {
+            command.env(key, threads.to_string());
+        }
+    }
+    // User-supplied variables come last so they can override the defaults above.
+    for (key, value) in setup
+        .extra_env
+        .iter()
+        .filter(|(key, _)| !key.trim().is_empty())
+    {
+        command.env(key.trim(), value);
+    }
+    command
+}
+
+/// Renders the command line [`spawn_process`] runs, in a form that can be
+/// pasted into a shell to reproduce the run by hand.
+pub fn build_command_preview(setup: &UserSetup, inp_path: &Path) -> String {
+    let command = build_command(setup, inp_path);
+    let mut parts = Vec::new();
+    if let Some(dir) = command.get_current_dir() {
+        parts.push(format!("cd {} &&", quote_arg(&dir.to_string_lossy())));
+    }
+    for (key, value) in command.get_envs() {
+        if let Some(value) = value {
+            parts.push(format!(
+                "{}={}",
+                key.to_string_lossy(),
+                quote_arg(&value.to_string_lossy())
+            ));
+        }
+    }
+    parts.push(quote_arg(&command.get_program().to_string_lossy()));
+    parts.extend(
+        command
+            .get_args()
+            .map(|arg| quote_arg(&arg.to_string_lossy())),
+    );
+    parts.join(" ")
+}
+
+/// Copies the deck into [`results_dir`] if that isn't the input's folder, so
+/// ccx finds it by its job name and writes its results next to the copy.
+/// ccx names its output files after the job argument, so passing the deck by
+/// its absolute path instead would put the results next to the input again.
+///
+/// The copy starts with a comment naming its source. A file without it is
+/// never overwritten, and relative `*INCLUDE`s are made absolute so they
+/// still resolve from the results directory.
+fn copy_deck_to_results_dir(setup: &UserSetup, inp_path: &Path) -> Result<(), std::io::Error> {
+    let dir = results_dir(setup, inp_path);
+    let input_dir = job_dir(setup, inp_path);
+    // Compared canonically: copying a file onto itself would truncate it.
+    if std::fs::canonicalize(dir)? == std::fs::canonicalize(input_dir)? {
+        return Ok(());
+    }
+    let target = dir.join(inp_path.file_name().unwrap_or_default());
+    let marker = format!(
+        "** Copied by ccx_runner from {}",
+        std::path::absolute(inp_path)?.display()
+    );
+    let ours = match std::fs::read_to_string(&target) {
+        Ok(existing) => existing.lines().next() == Some(marker.as_str()),
+        Err(e) => e.kind() == std::io::ErrorKind::NotFound,
+    };
+    if !ours {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!(
+                "{} exists and isn't a copy of {}; move it away or pick another working directory",
+                target.display(),
+                inp_path.display()
+            ),
+        ));
+    }
+    let deck = crate::inp::rebase_includes(inp_path, input_dir)?;
+    std::fs::write(&target, format!("{}\n{}", marker, deck))
+}
+
+pub fn spawn_process(setup: &UserSetup, inp_path: &Path) -> Result<Child, std::io::Error> {
+    copy_deck_to_results_dir(setup, inp_path)?;
+    remove_stale_progress_files(results_dir(setup, inp_path), job_name(inp_path))?;
+    let mut command = build_command(setup, inp_path);
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        // Run ccx in its own process group so its subprocesses can be killed with it.
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn()
+}
+
+/// Extracts e.g. `2.21` from the "This is Version 2.21" banner of `ccx -v`.
+pub fn parse_ccx_version(output: &str) -> Option<String> {
+    let mut words = output.split_whitespace();
+    words.find(|word| word.eq_ignore_ascii_case("version"))?;
+    let version = words.next()?.trim_end_matches([',', '.']);
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+/// Runs `<ccx> -v` and parses the reported version. Returns `None` if the
+/// binary can't be run, doesn't answer within a timeout, or isn't CalculiX.
+pub fn query_ccx_version(setup: &UserSetup) -> Option<String> {
+    let mut child = ccx_command(setup)
+        .arg("-v")
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .stderr(Stdio::null())
         .spawn()
+        .ok()?;
+
+    let started = std::time::Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < VERSION_QUERY_TIMEOUT => {
+                thread::sleep(EXIT_POLL_INTERVAL);
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    std::io::Read::read_to_string(&mut child.stdout.take()?, &mut output).ok()?;
+    parse_ccx_version(&output)
+}
+
+/// Opens `<job>.frd` in CGX. The viewer runs detached from the app.
+pub fn open_in_cgx(cgx_path: &Path, dir: &Path, job_name: &str) -> Result<(), std::io::Error> {
+    let mut child = Command::new(cgx_path)
+        .arg(format!("{}.frd", job_name))
+        .current_dir(dir)
+        .spawn()?;
+    // Reaped in the background, so a closed CGX doesn't linger as a zombie.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Kills the solver. On Unix the whole process group is signalled, since ccx
+/// may have launched subprocesses of its own.
+pub fn kill_process(child: &mut Child) -> Result<(), std::io::Error> {
+    #[cfg(unix)]
+    {
+        // The group id equals the child's pid because of `process_group(0)`.
+        let pgid = child.id() as libc::pid_t;
+        // SAFETY: `kill` has no memory-safety preconditions.
+        unsafe {
+            libc::kill(-pgid, libc::SIGKILL);
+        }
+    }
+    child.kill()
+}
+
+/// Reaps a killed solver on a detached thread, so it doesn't linger as a
+/// zombie if the reader thread is still blocked on its output.
+pub fn spawn_reaper_thread(process: Arc<Mutex<Child>>) {
+    thread::spawn(move || wait_for_exit(&process));
+}
+
+/// Blocks until the solver has exited and reaps it. Polls instead of
+/// `wait()` so the lock stays free for the Stop button and the other threads.
+/// `None` if the exit status can't be read.
+fn wait_for_exit(process: &Mutex<Child>) -> Option<ExitStatus> {
+    loop {
+        // The guard must be dropped before sleeping.
+        let state = process.lock().ok()?.try_wait();
+        match state {
+            Ok(None) => thread::sleep(EXIT_POLL_INTERVAL),
+            Ok(Some(status)) => return Some(status),
+            Err(_) => return None,
+        }
+    }
+}
+
+/// Number of log lines buffered before the log file is flushed to disk.
+const LOG_FLUSH_INTERVAL: usize = 100;
+
+/// How often the reader checks whether the solver has exited after stdout closed.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long `ccx -v` may take before it is considered hung.
+const VERSION_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often tailed files (`.sta`, `.cvg`) are polled for new rows.
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Per-run settings for the stdout reader thread.
+#[derive(Default)]
+pub struct ReaderOptions {
+    pub log_path: Option<PathBuf>,
+    pub step_periods: Vec<f64>,
+    /// Parse step, increment and residual info from stdout. Disabled when the
+    /// `.sta` file is used as the progress source instead.
+    pub parse_progress: bool,
+    /// Parse `largest residual` lines from stdout. Disabled when the residuals
+    /// are read from the `.cvg` file instead.
+    pub parse_residuals: bool,
+    /// Set once stdout has closed, i.e. the solver is done.
+    pub finished: Arc<AtomicBool>,
+    /// Kill the solver as soon as the input has been read, i.e. at the first
+    /// step or the first `*ERROR`.
+    pub check_only: bool,
+}
+
+pub fn log_file_path(project_dir: &Path, job_name: &str) -> PathBuf {
+    project_dir.join(format!("{}.log", job_name))
+}
+
+/// Like [`BufRead::lines`], but decodes invalid UTF-8 lossily instead of
+/// failing, and also strips the `\r` of CRLF line endings.
+fn lossy_lines<R: BufRead>(mut reader: R) -> impl Iterator<Item = std::io::Result<String>> {
+    let mut buf = Vec::new();
+    std::iter::from_fn(move || {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => None,
+            Ok(_) => {
+                if buf.ends_with(b"\n") {
+                    buf.pop();
+                    if buf.ends_with(b"\r") {
+                        buf.pop();
+                    }
+                }
+                Some(Ok(String::from_utf8_lossy(&buf).into_owned()))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    })
 }
 
-pub fn spawn_reader_thread(child: &mut Child, sender: Sender<SolverMessage>) {
-    let stdout = child.stdout.take().unwrap();
+/// Returns an error instead of spawning if the child's stdout isn't available.
+pub fn spawn_reader_thread(
+    process: &Arc<Mutex<Child>>,
+    sender: MessageSender,
+    options: ReaderOptions,
+) -> std::io::Result<()> {
+    let stdout = process
+        .lock()
+        .unwrap()
+        .stdout
+        .take()
+        .ok_or_else(|| missing_pipe_error("stdout"))?;
     let reader = BufReader::new(stdout);
+    let process = Arc::clone(process);
 
     thread::spawn(move || {
         let sender_clone = sender; // The move closure takes ownership of sender.
         let mut current_step_info: Option<StepInfo> = None;
         let mut total_iterations_for_residual = 0;
+        let mut log_writer = options.log_path.and_then(|path| match File::create(&path) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("Failed to create log file {}: {}", path.display(), e);
+                None
+            }
+        });
+        let mut lines_since_flush = 0;
+        let mut check_done = false;
+        let mut model_info = ModelInfo::default();
+        let mut pending_count: Option<ModelCount> = None;
+        // Sent once its detail lines are complete.
+        let mut pending_diagnostic: Option<Diagnostic> = None;
+        let mut increment_started: Option<(u32, Instant)> = None;
 
-        for line_result in reader.lines() {
+        for line_result in lossy_lines(reader) {
             match line_result {
                 Ok(line) => {
-                    if line.trim().starts_with("STEP") {
-                        if let Some(step_str) = line.split_whitespace().last() {
-                            if let Ok(step_num) = step_str.parse::<u32>() {
-                                let new_info = StepInfo {
-                                    step: step_num,
-                                    ..Default::default()
-                                };
-                                current_step_info = Some(new_info.clone());
-                                if sender_clone.send(SolverMessage::NewStepInfo(new_info)).is_err()
-                                {
-                                    break;
-                                }
+                    if let Some(writer) = log_writer.as_mut() {
+                        let _ = writeln!(writer, "{}", line);
+                        lines_since_flush += 1;
+                        if lines_since_flush >= LOG_FLUSH_INTERVAL {
+                            let _ = writer.flush();
+                            lines_since_flush = 0;
+                        }
+                    }
+
+                    if let Some(count) = pending_count.take() {
+                        if let Ok(value) = line.trim().parse::<u64>() {
+                            match count {
+                                ModelCount::Equations => model_info.num_equations = Some(value),
+                                ModelCount::Nonzeros => model_info.num_nonzeros = Some(value),
+                            }
+                            if sender_clone
+                                .send(SolverMessage::ModelInfo(model_info.clone()))
+                                .is_err()
+                            {
+                                break;
                             }
                         }
+                    }
+                    if line.trim() == "number of equations" {
+                        pending_count = Some(ModelCount::Equations);
+                    } else if line.trim().starts_with("number of nonzero") {
+                        pending_count = Some(ModelCount::Nonzeros);
+                    } else if let Some((report, megabytes)) = parse_memory_line(&line) {
+                        // Reported per solver call, so keep the peak.
+                        let peak = match report {
+                            MemoryReport::Needed => &mut model_info.memory_needed_mb,
+                            MemoryReport::Used => &mut model_info.memory_used_mb,
+                        };
+                        *peak = Some(peak.map_or(megabytes, |p| p.max(megabytes)));
+                        if sender_clone
+                            .send(SolverMessage::ModelInfo(model_info.clone()))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    } else if let Some((phase, secs)) = parse_timing_line(&line) {
+                        if sender_clone
+                            .send(SolverMessage::Timing { phase, secs })
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+
+                    let step_header = parse_step_header(&line);
+                    if let Some(step_num) = step_header.filter(|_| options.parse_progress) {
+                        let new_info = StepInfo {
+                            step: step_num,
+                            step_period: step_num
+                                .checked_sub(1)
+                                .and_then(|i| options.step_periods.get(i as usize))
+                                .copied(),
+                            ..Default::default()
+                        };
+                        current_step_info = Some(new_info.clone());
+                        if sender_clone
+                            .send(SolverMessage::NewStepInfo(new_info))
+                            .is_err()
+                        {
+                            break;
+                        }
                     } else if let Some(info) = current_step_info.as_mut() {
                         let mut updated = false;
-                        if line.trim().starts_with("increment ") {
+                        // Checked first, since it also starts with "increment".
+                        if line.starts_with(" increment size=") {
+                            if let Some(val_str) = line.split('=').nth(1) {
+                                if let Ok(val) = val_str.trim().parse::<f64>() {
+                                    info.dtime = val;
+                                    updated = true;
+                                }
+                            }
+                        } else if let Some((inc, att)) = parse_increment(&line) {
                             if sender_clone.send(SolverMessage::ResetResiduals).is_err() {
                                 break;
                             }
-                            total_iterations_for_residual = 0;
-                            let parts: Vec<&str> = line.split_whitespace().collect();
-                            if parts.len() >= 4 {
-                                if let (Ok(inc), Ok(att)) =
-                                    (parts[1].parse::<u32>(), parts[3].parse::<u32>())
-                                {
-                                    info.increment = inc;
-                                    info.attempt = att;
-                                    info.iterations = 0; // Reset for new attempt
-                                    updated = true;
+                            info.increment = inc;
+                            info.attempt = att;
+                            info.iterations = 0; // Reset for new attempt
+                            updated = true;
+
+                            let now = Instant::now();
+                            if let Some((previous, started)) = increment_started.replace((inc, now))
+                            {
+                                let timing = SolverMessage::IncrementTiming {
+                                    increment: previous,
+                                    secs: now.duration_since(started).as_secs_f64(),
+                                };
+                                if sender_clone.send(timing).is_err() {
+                                    break;
                                 }
                             }
                         } else if line.trim().starts_with("iteration ") {
                             info.iterations += 1;
+                            // Counted here so force and flux of one iteration share an x value.
+                            total_iterations_for_residual += 1;
                             updated = true;
+                        } else if line.starts_with(" actual step time=") {
+                            if let Some(val_str) = line.split('=').nth(1) {
+                                if let Ok(val) = val_str.trim().parse::<f64>() {
+                                    info.step_time = val;
+                                    updated = true;
+                                }
+                            }
                         } else if line.starts_with(" actual total time=") {
                             if let Some(val_str) = line.split('=').nth(1) {
                                 if let Ok(val) = val_str.trim().parse::<f64>() {
@@ -100,16 +881,32 @@ pub fn spawn_reader_thread(child: &mut Child, sender: Sender<SolverMessage>) {
                                     updated = true;
                                 }
                             }
-                        } else if line.trim().starts_with("largest residual force=") {
+                        } else if let Some(method) = solution_method(&line) {
+                            // Options such as line search come on top of the base method.
+                            match &mut info.method {
+                                Some(methods) if !methods.contains(method) => {
+                                    methods.push_str(" + ");
+                                    methods.push_str(method);
+                                }
+                                Some(_) => {}
+                                None => info.method = Some(method.to_string()),
+                            }
+                            updated = true;
+                        } else if let Some(kind) =
+                            residual_kind(&line).filter(|_| options.parse_residuals)
+                        {
                             if let Some(val_str) = line.split('=').nth(1) {
                                 if let Some(residual_str) = val_str.split_whitespace().next()
                                 {
                                     if let Ok(residual) = residual_str.parse::<f64>() {
-                                        total_iterations_for_residual += 1;
                                         let residual_data = ResidualData {
+                                            kind,
                                             step: info.step,
+                                            increment: info.increment,
+                                            attempt: info.attempt,
                                             total_iteration: total_iterations_for_residual,
                                             residual,
+                                            correction: None,
                                         };
                                         if sender_clone
                                             .send(SolverMessage::Residual(residual_data))
@@ -120,6 +917,9 @@ pub fn spawn_reader_thread(child: &mut Child, sender: Sender<SolverMessage>) {
                                     }
                                 }
                             }
+                        } else if is_cutback_line(&line) {
+                            info.cutbacks += 1;
+                            updated = true;
                         }
 
                         if updated
@@ -131,9 +931,51 @@ pub fn spawn_reader_thread(child: &mut Child, sender: Sender<SolverMessage>) {
                             }
                     }
 
+                    let severity = diagnostic_severity(&line);
+                    if let Some(diagnostic) = pending_diagnostic.as_mut() {
+                        let is_detail = severity.is_none()
+                            && !line.trim().is_empty()
+                            && !is_progress_line(&line)
+                            && diagnostic.detail.len() < MAX_DIAGNOSTIC_DETAIL_LINES;
+                        if is_detail {
+                            diagnostic.detail.push(line.trim().to_string());
+                        } else if let Some(diagnostic) = pending_diagnostic.take() {
+                            if sender_clone
+                                .send(SolverMessage::Diagnostic(diagnostic))
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    }
+                    if let Some(severity) = severity {
+                        pending_diagnostic = Some(Diagnostic {
+                            severity,
+                            message: line.trim().to_string(),
+                            detail: Vec::new(),
+                        });
+                    }
+
+                    let reached_step = step_header.is_some();
+                    let check_triggered = options.check_only
+                        && !check_done
+                        && (reached_step || severity == Some(Severity::Error));
+
                     if sender_clone.send(SolverMessage::Line(line)).is_err() {
                         break; // Receiver has been dropped
                     }
+
+                    if check_triggered {
+                        check_done = true;
+                        let checked = SolverMessage::InputChecked { reached_step };
+                        if sender_clone.send(checked).is_err() {
+                            break;
+                        }
+                        // Keep reading, so the remaining output and the exit status still arrive.
+                        if let Err(e) = kill_process(&mut process.lock().unwrap()) {
+                            eprintln!("Failed to stop input check: {}", e);
+                        }
+                    }
                 }
                 Err(e) => {
                     eprintln!("Error reading line: {}", e);
@@ -141,5 +983,471 @@ pub fn spawn_reader_thread(child: &mut Child, sender: Sender<SolverMessage>) {
                 }
             }
         }
+
+        if let Some(diagnostic) = pending_diagnostic {
+            let _ = sender_clone.send(SolverMessage::Diagnostic(diagnostic));
+        }
+        // The last increment lasts until the output ends.
+        if let Some((increment, started)) = increment_started {
+            let _ = sender_clone.send(SolverMessage::IncrementTiming {
+                increment,
+                secs: started.elapsed().as_secs_f64(),
+            });
+        }
+
+        // The loop also ends when the process is killed, so the log is complete either way.
+        if let Some(mut writer) = log_writer {
+            let _ = writer.flush();
+        }
+
+        if let Some(status) = wait_for_exit(&process) {
+            let _ = sender_clone.send(SolverMessage::Finished(status));
+        }
+        options.finished.store(true, Ordering::SeqCst);
+    });
+    Ok(())
+}
+
+/// Extensions of the files ccx writes next to the input deck.
+const ARTIFACT_EXTENSIONS: [&str; 7] = ["frd", "dat", "sta", "cvg", "12d", "eig", "out"];
+
+/// Existing files in `dir` that ccx generated for the job `stem`.
+pub fn job_artifacts(dir: &Path, stem: &str) -> Vec<PathBuf> {
+    ARTIFACT_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+pub fn sta_file_path(project_dir: &Path, job_name: &str) -> PathBuf {
+    project_dir.join(format!("{}.sta", job_name))
+}
+
+pub fn cvg_file_path(project_dir: &Path, job_name: &str) -> PathBuf {
+    project_dir.join(format!("{}.cvg", job_name))
+}
+
+/// Parses one data row of a `.sta` file:
+/// `STEP INC ATT ITRS TOT-TIME STEP-TIME INC-TIME`.
+/// The attempt column may carry a trailing `U` for unconverged attempts.
+pub fn parse_sta_line(line: &str) -> Option<StepInfo> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 7 {
+        return None;
+    }
+    let attempt = parts[2].trim_end_matches(|c: char| !c.is_ascii_digit());
+    Some(StepInfo {
+        step: parts[0].parse().ok()?,
+        increment: parts[1].parse().ok()?,
+        attempt: attempt.parse().ok()?,
+        iterations: parts[3].parse().ok()?,
+        total_time: parts[4].parse().ok()?,
+        step_time: parts[5].parse().ok()?,
+        dtime: parts[6].parse().ok()?,
+        ..Default::default()
+    })
+}
+
+/// Identifies the file behind a path, to notice when it is replaced.
+#[cfg(unix)]
+fn file_identity(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Polls `path` until `finished` is set, then reads it one last time, and
+/// hands every complete line added since the previous poll to `on_line`,
+/// which returns `false` to stop. Only the bytes past the last read are read;
+/// the file is read from the start again if it shrinks or is replaced.
+fn tail_file(path: &Path, finished: &AtomicBool, mut on_line: impl FnMut(&str) -> bool) {
+    let mut offset = 0;
+    let mut identity = None;
+    // Bytes of a line that is still being written.
+    let mut partial = Vec::new();
+
+    loop {
+        let done = finished.load(Ordering::SeqCst);
+
+        if let Ok(mut file) = File::open(path) {
+            if let Ok(metadata) = file.metadata() {
+                let current = file_identity(&metadata);
+                if metadata.len() < offset || current != identity {
+                    // Truncated or recreated, e.g. by ccx restarting it.
+                    offset = 0;
+                    partial.clear();
+                    identity = current;
+                }
+                let mut added = Vec::new();
+                if file.seek(SeekFrom::Start(offset)).is_ok()
+                    && file.read_to_end(&mut added).is_ok()
+                {
+                    offset += added.len() as u64;
+                    partial.extend_from_slice(&added);
+                    // Only complete lines are parsed; a partially written one waits.
+                    let complete = partial
+                        .iter()
+                        .rposition(|&b| b == b'\n')
+                        .map_or(0, |i| i + 1);
+                    let lines: Vec<u8> = partial.drain(..complete).collect();
+                    for line in String::from_utf8_lossy(&lines).lines() {
+                        if !on_line(line) {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+
+        if done {
+            break;
+        }
+        thread::sleep(TAIL_POLL_INTERVAL);
+    }
+}
+
+/// Removes the `.sta` and `.cvg` files of an earlier run of the job, so that
+/// tailing them can't report stale progress before ccx writes new ones.
+fn remove_stale_progress_files(dir: &Path, job_name: &str) -> std::io::Result<()> {
+    for path in [sta_file_path(dir, job_name), cvg_file_path(dir, job_name)] {
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Tails `<job>.sta` and emits the same step messages the stdout parser
+/// would. Polls until `finished` is set, then reads the file one last time.
+pub fn spawn_sta_reader_thread(
+    sta_path: PathBuf,
+    sender: MessageSender,
+    step_periods: Vec<f64>,
+    finished: Arc<AtomicBool>,
+) {
+    thread::spawn(move || {
+        let mut current_step = None;
+        let mut step_cutbacks = 0;
+
+        tail_file(&sta_path, &finished, |line| {
+            let Some(mut info) = parse_sta_line(line) else {
+                return true;
+            };
+            info.step_period = info
+                .step
+                .checked_sub(1)
+                .and_then(|i| step_periods.get(i as usize))
+                .copied();
+            if current_step != Some(info.step) {
+                current_step = Some(info.step);
+                step_cutbacks = 0;
+                let new_info = StepInfo {
+                    step: info.step,
+                    step_period: info.step_period,
+                    ..Default::default()
+                };
+                if sender.send(SolverMessage::NewStepInfo(new_info)).is_err() {
+                    return false;
+                }
+            }
+            // Every attempt beyond the first was a cutback.
+            step_cutbacks += info.attempt.saturating_sub(1);
+            info.cutbacks = step_cutbacks;
+            sender.send(SolverMessage::UpdateStepInfo(info)).is_ok()
+        });
+    });
+}
+
+/// Parses one data row of a `.cvg` file:
+/// `STEP INC ATT ITER CONT.EL. RESID.FORCE CORR.DISP RESID.FLUX CORR.TEMP`,
+/// with residuals and corrections in percent. Yields the force residual and,
+/// for thermal steps, the flux residual. `total_iteration` is left at 0 for
+/// the caller to count.
+pub fn parse_cvg_line(line: &str) -> Vec<ResidualData> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 9 {
+        return Vec::new();
+    }
+    let attempt = parts[2].trim_end_matches(|c: char| !c.is_ascii_digit());
+    let (Ok(step), Ok(increment), Ok(attempt)) =
+        (parts[0].parse(), parts[1].parse(), attempt.parse())
+    else {
+        return Vec::new();
+    };
+    let Some(values) = parts[5..9]
+        .iter()
+        .map(|part| part.parse::<f64>().ok())
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Vec::new();
+    };
+
+    let residual = |kind, residual, correction| ResidualData {
+        kind,
+        step,
+        increment,
+        attempt,
+        total_iteration: 0,
+        residual,
+        correction: Some(correction),
+    };
+    let has_force = values[0] != 0.0 || values[1] != 0.0;
+    let has_flux = values[2] != 0.0 || values[3] != 0.0;
+    let mut residuals = Vec::new();
+    if has_force || !has_flux {
+        residuals.push(residual(ResidualKind::Force, values[0], values[1]));
+    }
+    if has_flux {
+        residuals.push(residual(ResidualKind::Flux, values[2], values[3]));
+    }
+    residuals
+}
+
+/// Tails `<job>.cvg` and emits its residuals and corrections, which cover
+/// every iteration rather than just the `largest residual` lines on stdout.
+pub fn spawn_cvg_reader_thread(
+    cvg_path: PathBuf,
+    sender: MessageSender,
+    finished: Arc<AtomicBool>,
+) {
+    thread::spawn(move || {
+        let mut total_iteration = 0;
+        let mut current_attempt = None;
+
+        tail_file(&cvg_path, &finished, |line| {
+            let residuals = parse_cvg_line(line);
+            let Some(first) = residuals.first() else {
+                return true;
+            };
+            let attempt = Some((first.step, first.increment, first.attempt));
+            if attempt != current_attempt {
+                current_attempt = attempt;
+                if sender.send(SolverMessage::ResetResiduals).is_err() {
+                    return false;
+                }
+            }
+            total_iteration += 1;
+            residuals.into_iter().all(|mut residual| {
+                residual.total_iteration = total_iteration;
+                sender.send(SolverMessage::Residual(residual)).is_ok()
+            })
+        });
+    });
+}
+
+/// Returns an error instead of spawning if the child's stderr isn't available.
+pub fn spawn_stderr_reader_thread(child: &mut Child, sender: MessageSender) -> std::io::Result<()> {
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| missing_pipe_error("stderr"))?;
+    let reader = BufReader::new(stderr);
+
+    thread::spawn(move || {
+        for line_result in lossy_lines(reader) {
+            match line_result {
+                Ok(line) => {
+                    if let Some(severity) = diagnostic_severity(&line) {
+                        let diagnostic = Diagnostic {
+                            severity,
+                            message: line.trim().to_string(),
+                            detail: Vec::new(),
+                        };
+                        if sender.send(SolverMessage::Diagnostic(diagnostic)).is_err() {
+                            break;
+                        }
+                    }
+                    if sender.send(SolverMessage::StderrLine(line)).is_err() {
+                        break; // Receiver has been dropped
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error reading stderr line: {}", e);
+                    break;
+                }
+            }
+        }
     });
-}
\ No newline at end of file
+    Ok(())
+}
+
+fn missing_pipe_error(stream: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::BrokenPipe,
+        format!("the solver's {} was not captured", stream),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_progress_line, parse_cvg_line, parse_increment, parse_memory_line, parse_step_header,
+        solution_method, MemoryReport, ResidualKind,
+    };
+
+    /// Step headers as they appear in ccx's stdout.
+    const STEP_HEADERS: [(&str, u32); 5] = [
+        (" STEP            1", 1),
+        (" STEP 2", 2),
+        ("STEP   12  ", 12),
+        (" S T E P   3", 3),
+        (" -------- STEP 4 --------", 4),
+    ];
+
+    /// Lines of ccx's stdout that mention steps without starting one.
+    const NOT_STEP_HEADERS: [&str; 6] = [
+        " actual step time=1.000000e+00",
+        " Job finished",
+        " STEP",
+        "STEPS 1",
+        " *INFO: STEP 1 has no load",
+        " increment 1 attempt 1 ",
+    ];
+
+    /// Increment headers as printed by different ccx versions.
+    const INCREMENT_LINES: [(&str, (u32, u32)); 6] = [
+        (" increment 1 attempt 1 ", (1, 1)),
+        (" increment 12 attempt 3", (12, 3)),
+        ("increment     7   attempt     2  ", (7, 2)),
+        (" increment 4, attempt 1", (4, 1)),
+        ("\tincrement 5\tattempt 2", (5, 2)),
+        (" increment 9", (9, 1)),
+    ];
+
+    /// Lines of ccx's stdout that mention increments without starting one.
+    const NOT_INCREMENT_LINES: [&str; 6] = [
+        " increment size= 1.000000e-01",
+        " increment size is decreased to 5.000000e-02",
+        " convergence; the increment size will be divided by 4",
+        " number of increments: 3",
+        " increment attempt 1",
+        " STEP 1",
+    ];
+
+    #[test]
+    fn parses_increment_lines() {
+        for (line, expected) in INCREMENT_LINES {
+            assert_eq!(parse_increment(line), Some(expected), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn ignores_non_increment_lines() {
+        for line in NOT_INCREMENT_LINES {
+            assert_eq!(parse_increment(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn recognizes_solution_method_banners() {
+        let banners = [
+            (
+                " Newton-Raphson iterative procedure is active.",
+                "Newton-Raphson",
+            ),
+            (" QUASINEWTON iterations are used", "Quasi-Newton"),
+            (" LINE SEARCH is active", "Line search"),
+        ];
+        for (line, method) in banners {
+            assert_eq!(solution_method(line), Some(method), "{:?}", line);
+        }
+        assert_eq!(solution_method(" Static analysis was selected"), None);
+    }
+
+    #[test]
+    fn progress_lines_end_diagnostic_detail() {
+        for line in [" STEP 2", " increment 3 attempt 1 ", " iteration 4"] {
+            assert!(is_progress_line(line), "{:?}", line);
+        }
+        for line in [
+            " in line 12 of file job.inp",
+            " card image: *BOUNDARY",
+            " increment size is decreased to 5.000000e-02",
+        ] {
+            assert!(!is_progress_line(line), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn parses_memory_lines() {
+        let reports = [
+            (
+                " Using up to 1234 MB of memory",
+                (MemoryReport::Used, 1234.0),
+            ),
+            (" Memory needed:2.5 GB", (MemoryReport::Needed, 2560.0)),
+            (
+                "  memory required =   512   kB",
+                (MemoryReport::Needed, 0.5),
+            ),
+        ];
+        for (line, expected) in reports {
+            assert_eq!(parse_memory_line(line), Some(expected), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn ignores_non_memory_lines() {
+        for line in [
+            " Memory allocation done",
+            " using up to 8 cpus",
+            " 12 MB written to job.frd",
+        ] {
+            assert_eq!(parse_memory_line(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn parses_cvg_rows() {
+        let mechanical = parse_cvg_line(
+            "      1     2    1     3       0   0.1234E+01   0.5000E+02  0.0000E+00  0.0000E+00",
+        );
+        assert_eq!(mechanical.len(), 1);
+        assert_eq!(mechanical[0].kind, ResidualKind::Force);
+        assert_eq!(
+            (
+                mechanical[0].step,
+                mechanical[0].increment,
+                mechanical[0].attempt
+            ),
+            (1, 2, 1)
+        );
+        assert_eq!(mechanical[0].residual, 1.234);
+        assert_eq!(mechanical[0].correction, Some(50.0));
+
+        let coupled = parse_cvg_line(
+            "      2     1    3     1       0   0.1000E+00   0.2000E+00  0.3000E+00  0.4000E+00",
+        );
+        let kinds: Vec<_> = coupled.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, [ResidualKind::Force, ResidualKind::Flux]);
+        assert_eq!(coupled[1].residual, 0.3);
+        assert_eq!(coupled[1].correction, Some(0.4));
+    }
+
+    #[test]
+    fn skips_cvg_headers() {
+        assert!(parse_cvg_line(" STEP INC ATT ITER CONT. RESID. CORR. RESID. CORR.").is_empty());
+        assert!(parse_cvg_line("                EL.  FORCE  DISP  FLUX  TEMP.").is_empty());
+        assert!(parse_cvg_line("").is_empty());
+    }
+
+    #[test]
+    fn parses_step_headers() {
+        for (line, step) in STEP_HEADERS {
+            assert_eq!(parse_step_header(line), Some(step), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        for line in NOT_STEP_HEADERS {
+            assert_eq!(parse_step_header(line), None, "{:?}", line);
+        }
+    }
+}